@@ -1,11 +1,23 @@
 use super::*;
 use alloc::boxed::Box;
+use alloc::sync::Arc;
 use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::Range;
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::InOrder {}
+    impl Sealed for super::PreOrder {}
+    impl Sealed for super::PostOrder {}
+}
 
 ///Specified which type of dfs order we want. In order/pre order/post order.
-trait DfsOrder: Clone {
+///Implemented by InOrder, PreOrder and PostOrder. It is sealed, so it cannot be implemented outside this crate.
+pub trait DfsOrder: sealed::Sealed + Clone {
     fn split_mut<T>(nodes: &mut [T]) -> (&mut T, &mut [T], &mut [T]);
     fn split<T>(nodes: &[T]) -> (&T, &[T], &[T]);
+    fn split_range(range: Range<usize>) -> (usize, Range<usize>, Range<usize>);
 }
 
 ///Pass this to the tree for In order layout
@@ -24,6 +36,10 @@ impl DfsOrder for InOrder {
         let (middle, right) = rest.split_first().unwrap();
         (middle, left, right)
     }
+    fn split_range(range: Range<usize>) -> (usize, Range<usize>, Range<usize>) {
+        let mid = range.start + range.len() / 2;
+        (mid, range.start..mid, mid + 1..range.end)
+    }
 }
 
 ///Pass this to the tree for pre order layout
@@ -42,6 +58,10 @@ impl DfsOrder for PreOrder {
         let (left, right) = rest.split_at(mm);
         (middle, left, right)
     }
+    fn split_range(range: Range<usize>) -> (usize, Range<usize>, Range<usize>) {
+        let mm = range.start + 1 + range.len() / 2;
+        (range.start, range.start + 1..mm, mm..range.end)
+    }
 }
 
 ///Pass this to the tree for post order layout
@@ -60,6 +80,10 @@ impl DfsOrder for PostOrder {
         let (left, right) = rest.split_at(mm);
         (middle, left, right)
     }
+    fn split_range(range: Range<usize>) -> (usize, Range<usize>, Range<usize>) {
+        let mm = range.start + range.len() / 2;
+        (range.end - 1, range.start..mm, mm..range.end - 1)
+    }
 }
 
 ///Container for a dfs order tree. Internally uses a Vec. Derefs to a CompleteTree.
@@ -88,6 +112,28 @@ impl<T> CompleteTreeContainer<T, InOrder> {
     }
 }
 
+impl<T: Ord> CompleteTreeContainer<T, InOrder> {
+    ///Creates a binary search tree from sorted elements.
+    ///The in order layout of a binary search tree is exactly the sorted order,
+    ///so the elements are used as they are.
+    #[inline]
+    pub fn from_sorted(vec: Vec<T>) -> Result<CompleteTreeContainer<T, InOrder>, FromSortedError> {
+        if !is_sorted(vec.iter()) {
+            return Err(FromSortedError::NotSorted);
+        }
+        CompleteTreeContainer::from_inorder(vec).map_err(FromSortedError::NotCompleteTreeSize)
+    }
+}
+
+///Error returned by CompleteTreeContainer::from_sorted().
+#[derive(Copy, Clone, Debug)]
+pub enum FromSortedError {
+    ///The length is not 2^n-1 for some n>0.
+    NotCompleteTreeSize(NotCompleteTreeSizeErr),
+    ///The elements are not in non decreasing order.
+    NotSorted,
+}
+
 impl<T> CompleteTreeContainer<T, PostOrder> {
     #[inline]
     pub fn from_postorder(
@@ -104,14 +150,60 @@ impl<T, D> CompleteTreeContainer<T, D> {
         self.nodes
     }
 
-    pub fn as_tree(&self) -> CompleteTree<T, D> {
+    ///Consumes the container, returning the root element and, if the root is not a leaf,
+    ///containers for its left and right subtrees.
+    pub fn into_root_and_children(self) -> (T, Option<(Self, Self)>)
+    where
+        D: DfsOrder,
+    {
+        let mut nodes = self.nodes.into_vec();
+        if nodes.len() == 1 {
+            return (nodes.pop().unwrap(), None);
+        }
+        let (middle, left, _) = D::split_range(0..nodes.len());
+        let mut root = None;
+        let mut left_nodes = Vec::with_capacity(left.len());
+        let mut right_nodes = Vec::with_capacity(left.len());
+        for (i, a) in nodes.into_iter().enumerate() {
+            if i == middle {
+                root = Some(a);
+            } else if left.contains(&i) {
+                left_nodes.push(a);
+            } else {
+                right_nodes.push(a);
+            }
+        }
+        let child = |nodes: Vec<T>| CompleteTreeContainer {
+            _p: PhantomData,
+            nodes: nodes.into_boxed_slice(),
+        };
+        (root.unwrap(), Some((child(left_nodes), child(right_nodes))))
+    }
+
+    ///Consumes the container, returning a visitor that moves each element out.
+    ///The visitor is Send if T is, so its children can be traversed on other threads.
+    #[inline]
+    pub fn into_vistr(self) -> IntoVistr<T, D> {
+        let mut nodes = ManuallyDrop::new(self.nodes.into_vec());
+        let range = 0..nodes.len();
+        IntoVistr {
+            _p: PhantomData,
+            buffer: Arc::new(IntoVistrBuffer {
+                ptr: nodes.as_mut_ptr(),
+                cap: nodes.capacity(),
+            }),
+            range,
+        }
+    }
+
+    pub fn as_tree(&self) -> CompleteTree<'_, T, D> {
         CompleteTree {
             _p: PhantomData,
             nodes: &self.nodes,
         }
     }
 
-    pub fn as_tree_mut(&mut self) -> CompleteTreeMut<T, D> {
+    pub fn as_tree_mut(&mut self) -> CompleteTreeMut<'_, T, D> {
         CompleteTreeMut {
             _p: PhantomData,
             nodes: &mut self.nodes,
@@ -132,6 +224,160 @@ impl<T, D> CompleteTreeContainer<T, D> {
     }
 }
 
+impl<T: PartialEq, D> PartialEq for CompleteTreeContainer<T, D> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+impl<T: Eq, D> Eq for CompleteTreeContainer<T, D> {}
+
+///Trees are compared by lexicographically comparing their elements in dfs preorder.
+///This is a structural ordering, not a semantic one.
+impl<T: PartialOrd, D: DfsOrder> PartialOrd for CompleteTreeContainer<T, D> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        preorder_iter::<_, D>(&self.nodes).partial_cmp(preorder_iter::<_, D>(&other.nodes))
+    }
+}
+
+///Trees are compared by lexicographically comparing their elements in dfs preorder.
+///This is a structural ordering, not a semantic one.
+impl<T: Ord, D: DfsOrder> Ord for CompleteTreeContainer<T, D> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        preorder_iter::<_, D>(&self.nodes).cmp(preorder_iter::<_, D>(&other.nodes))
+    }
+}
+
+//Iterates over the nodes of a tree laid out in order D in dfs preorder.
+fn preorder_iter<T, D: DfsOrder>(nodes: &[T]) -> impl Iterator<Item = &T> {
+    let mut stack = Vec::new();
+    if !nodes.is_empty() {
+        stack.push(nodes);
+    }
+    core::iter::from_fn(move || {
+        let remaining = stack.pop()?;
+        if remaining.len() == 1 {
+            Some(&remaining[0])
+        } else {
+            let (middle, left, right) = D::split(remaining);
+            stack.push(right);
+            stack.push(left);
+            Some(middle)
+        }
+    })
+}
+
+///Error returned by TreeBuilder::build().
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    ///No levels were pushed.
+    NoLevels,
+    ///The level at this depth did not have 2^depth elements.
+    WrongLevelSize {
+        depth: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+///Builds a tree one level at a time, starting from the root.
+///The levels are then laid out in whatever dfs order is requested.
+pub struct TreeBuilder<T> {
+    nodes: Vec<T>,
+    height: usize,
+    error: Option<BuildError>,
+}
+
+impl<T> Default for TreeBuilder<T> {
+    fn default() -> Self {
+        TreeBuilder::new()
+    }
+}
+
+impl<T> TreeBuilder<T> {
+    #[inline]
+    pub fn new() -> TreeBuilder<T> {
+        TreeBuilder {
+            nodes: Vec::new(),
+            height: 0,
+            error: None,
+        }
+    }
+
+    ///Adds the next level of the tree, from left to right.
+    ///The level at depth d must have 2^d elements, otherwise build() will return an error.
+    pub fn push_level(&mut self, items: impl IntoIterator<Item = T>) {
+        let depth = self.height;
+        let before = self.nodes.len();
+        self.nodes.extend(items);
+        let found = self.nodes.len() - before;
+        let expected = 1 << depth;
+        if found != expected && self.error.is_none() {
+            self.error = Some(BuildError::WrongLevelSize {
+                depth,
+                expected,
+                found,
+            });
+        }
+        self.height += 1;
+    }
+
+    ///Lays out the levels pushed so far in the dfs order D.
+    pub fn build<D: DfsOrder>(self) -> Result<CompleteTreeContainer<T, D>, BuildError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if self.nodes.is_empty() {
+            return Err(BuildError::NoLevels);
+        }
+
+        let mut bfs: Vec<_> = self.nodes.into_iter().map(Some).collect();
+        let mut nodes: Vec<_> = (0..bfs.len()).map(|_| None).collect();
+        place_bfs::<_, D>(&mut nodes, &mut bfs, 0);
+
+        Ok(CompleteTreeContainer {
+            _p: PhantomData,
+            nodes: nodes.into_iter().map(|a| a.unwrap()).collect(),
+        })
+    }
+}
+
+impl<T, D: DfsOrder> CompleteTreeContainer<T, D> {
+    //Moves every element to where the layout D2 puts it.
+    pub(crate) fn into_layout<D2: DfsOrder>(self) -> CompleteTreeContainer<T, D2> {
+        let mut src: Vec<_> = self.nodes.into_vec().into_iter().map(Some).collect();
+        let mut dst: Vec<_> = (0..src.len()).map(|_| None).collect();
+        relayout::<_, D, D2>(&mut src, &mut dst);
+
+        CompleteTreeContainer {
+            _p: PhantomData,
+            nodes: dst.into_iter().map(|a| a.unwrap()).collect(),
+        }
+    }
+}
+
+fn relayout<T, D: DfsOrder, D2: DfsOrder>(src: &mut [Option<T>], dst: &mut [Option<T>]) {
+    let (smiddle, sleft, sright) = D::split_mut(src);
+    let (dmiddle, dleft, dright) = D2::split_mut(dst);
+    *dmiddle = smiddle.take();
+    if !sleft.is_empty() {
+        relayout::<_, D, D2>(sleft, dleft);
+        relayout::<_, D, D2>(sright, dright);
+    }
+}
+
+//Moves the subtree rooted at bfs index k into the dfs layout D.
+fn place_bfs<T, D: DfsOrder>(nodes: &mut [Option<T>], bfs: &mut [Option<T>], k: usize) {
+    let (middle, left, right) = D::split_mut(nodes);
+    *middle = bfs[k].take();
+    if !left.is_empty() {
+        place_bfs::<_, D>(left, bfs, 2 * k + 1);
+        place_bfs::<_, D>(right, bfs, 2 * k + 2);
+    }
+}
+
 ///Complete binary tree stored in DFS inorder order.
 ///Height is atleast 1.
 #[repr(transparent)]
@@ -165,6 +411,45 @@ impl<'a, T> CompleteTree<'a, T, PostOrder> {
     }
 }
 
+impl<'a, T: Ord> CompleteTree<'a, T, InOrder> {
+    ///Returns true if the tree is a binary search tree.
+    ///Since the nodes are laid out in order, this just checks that they are sorted.
+    #[inline]
+    pub fn is_bst(&self) -> bool {
+        self.nodes.windows(2).all(|w| w[0] <= w[1])
+    }
+}
+impl<'a, T: Ord> CompleteTree<'a, T, PreOrder> {
+    ///Returns true if the tree is a binary search tree.
+    ///Checks that an inorder traversal of the tree is sorted.
+    #[inline]
+    pub fn is_bst(&self) -> bool {
+        is_sorted(self.borrow().vistr().dfs_inorder_iter())
+    }
+}
+impl<'a, T: Ord> CompleteTree<'a, T, PostOrder> {
+    ///Returns true if the tree is a binary search tree.
+    ///Checks that an inorder traversal of the tree is sorted.
+    #[inline]
+    pub fn is_bst(&self) -> bool {
+        is_sorted(self.borrow().vistr().dfs_inorder_iter())
+    }
+}
+
+fn is_sorted<T: Ord>(mut it: impl Iterator<Item = T>) -> bool {
+    let mut prev = match it.next() {
+        Some(a) => a,
+        None => return true,
+    };
+    for a in it {
+        if a < prev {
+            return false;
+        }
+        prev = a;
+    }
+    true
+}
+
 pub struct CompleteTreeMut<'a, T, D> {
     _p: PhantomData<D>,
     nodes: &'a mut [T],
@@ -205,14 +490,14 @@ impl<'a, T, D> From<CompleteTreeMut<'a, T, D>> for CompleteTree<'a, T, D> {
 }
 
 impl<'a, T, D> CompleteTreeMut<'a, T, D> {
-    pub fn as_tree(&self) -> CompleteTree<T, D> {
+    pub fn as_tree(&self) -> CompleteTree<'_, T, D> {
         CompleteTree {
             _p: PhantomData,
             nodes: self.nodes,
         }
     }
 
-    pub fn borrow_mut(&mut self)->CompleteTreeMut<T,D>{
+    pub fn borrow_mut(&mut self)->CompleteTreeMut<'_,T,D>{
         CompleteTreeMut{
             _p:PhantomData,
             nodes:self.nodes
@@ -236,6 +521,52 @@ impl<'a, T, D> CompleteTreeMut<'a, T, D> {
         self.nodes
     }
 
+    ///Returns the root element without creating a visitor.
+    #[inline]
+    pub fn root_mut(self) -> &'a mut T
+    where
+        D: DfsOrder,
+    {
+        D::split_mut(self.nodes).0
+    }
+
+    ///Swaps the two child subtrees of the node at the end of the path from the root.
+    ///Since both subtrees are contiguous and the same size, this is a swap of two ranges of the slice.
+    ///Does nothing if the path leads to a leaf. Panics if the path goes past a leaf.
+    pub fn swap_subtrees(&mut self, path: &[Branch])
+    where
+        D: DfsOrder,
+    {
+        let mut nodes = &mut *self.nodes;
+        for branch in path {
+            assert!(nodes.len() > 1, "path goes past a leaf");
+            let (_, left, right) = D::split_mut(nodes);
+            nodes = match branch {
+                Branch::Left => left,
+                Branch::Right => right,
+            };
+        }
+        if nodes.len() > 1 {
+            let (_, left, right) = D::split_mut(nodes);
+            left.swap_with_slice(right);
+        }
+    }
+
+    ///Creates a tree from a slice without checking that its length is a complete tree size.
+    ///Useful for turning the slice returned by get_nodes_mut() back into a tree in hot loops.
+    ///
+    /// # Safety
+    ///
+    ///The length of arr must be 2^n-1 for some n>0, and the slice must be laid out in the order D.
+    #[inline]
+    pub unsafe fn from_nodes_mut_unchecked(arr: &'a mut [T]) -> CompleteTreeMut<'a, T, D> {
+        debug_assert!(valid_node_num(arr.len()).is_ok());
+        CompleteTreeMut {
+            _p: PhantomData,
+            nodes: arr,
+        }
+    }
+
     #[inline]
     pub fn vistr_mut(self) -> VistrMut<'a,T, D> {
         VistrMut {
@@ -257,7 +588,7 @@ impl<'a, T, D> CompleteTree<'a, T, D> {
         })
     }
 
-    pub fn borrow(&self)->CompleteTree<T,D>{
+    pub fn borrow(&self)->CompleteTree<'_,T,D>{
         CompleteTree{
             _p:PhantomData,
             nodes:self.nodes
@@ -274,6 +605,15 @@ impl<'a, T, D> CompleteTree<'a, T, D> {
         self.nodes
     }
 
+    ///Returns the root element without creating a visitor.
+    #[inline]
+    pub fn root(self) -> &'a T
+    where
+        D: DfsOrder,
+    {
+        D::split(self.nodes).0
+    }
+
     #[inline]
     pub fn vistr(self) -> Vistr<'a,T, D> {
         Vistr {
@@ -285,7 +625,6 @@ impl<'a, T, D> CompleteTree<'a, T, D> {
 
 ///Tree visitor that returns a reference to each element in the tree.
 #[repr(transparent)]
-
 pub struct Vistr<'a, T: 'a, D> {
     _p: PhantomData<D>,
     remaining: &'a [T],
@@ -302,7 +641,7 @@ impl<'a, T: 'a, D> Clone for Vistr<'a, T, D> {
 
 impl<'a, T: 'a, D> Vistr<'a, T, D> {
     #[inline]
-    pub fn borrow(&self) -> Vistr<T, D> {
+    pub fn borrow(&self) -> Vistr<'_, T, D> {
         Vistr {
             _p: PhantomData,
             remaining: self.remaining,
@@ -313,6 +652,45 @@ impl<'a, T: 'a, D> Vistr<'a, T, D> {
     pub fn into_slice(self) -> &'a [T] {
         self.remaining
     }
+
+    ///Iterates over the elements in the order they are laid out, which is the dfs order D.
+    ///This iterates the slice directly.
+    #[inline]
+    pub fn iter_layout(self) -> core::slice::Iter<'a, T> {
+        self.remaining.iter()
+    }
+
+    ///Returns the number of nodes in the tree below and including this visitor.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    ///A visitor always points to at least one node, so this always returns false.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    ///Returns the number of nodes in the left and right subtrees, or None if this is a leaf.
+    ///Since the tree is complete, the two are always equal.
+    #[inline]
+    pub fn child_sizes(&self) -> Option<(usize, usize)> {
+        child_sizes(self.remaining.len())
+    }
+    ///Combine this visitor with a mutable visitor, to read from this tree while writing to the other.
+    ///Produces (&T, &mut U) pairs.
+    #[inline]
+    pub fn zip_mut<'b, U: 'b, D2>(
+        self,
+        other: VistrMut<'b, U, D2>,
+    ) -> Zip<Vistr<'a, T, D>, VistrMut<'b, U, D2>>
+    where
+        Vistr<'a, T, D>: Visitor<Item = &'a T>,
+        VistrMut<'b, U, D2>: Visitor<Item = &'b mut U>,
+    {
+        self.zip(other)
+    }
 }
 
 impl<'a, T: 'a> Visitor for Vistr<'a, T, PreOrder> {
@@ -379,6 +757,15 @@ impl<'a, T: 'a> Visitor for Vistr<'a, T, PostOrder> {
     }
 }
 
+fn child_sizes(len: usize) -> Option<(usize, usize)> {
+    if len <= 1 {
+        None
+    } else {
+        let half = (len - 1) / 2;
+        Some((half, half))
+    }
+}
+
 //TODO put this somewhere else
 fn log_2(x: usize) -> usize {
     const fn num_bits<T>() -> usize {
@@ -394,7 +781,7 @@ fn vistr_dfs_level_remaining_hint<T, D: DfsOrder>(vistr: &Vistr<T, D>) -> (usize
     //let left = ((vistr.remaining.len() + 1) as f64).log2() as usize;
     (left, Some(left))
 }
-fn vistr_next<T, D: DfsOrder>(vistr: Vistr<T, D>) -> (&T, Option<[Vistr<T, D>; 2]>) {
+fn vistr_next<T, D: DfsOrder>(vistr: Vistr<'_, T, D>) -> (&T, Option<[Vistr<'_, T, D>; 2]>) {
     let remaining = vistr.remaining;
     if remaining.len() == 1 {
         (&remaining[0], None)
@@ -421,6 +808,164 @@ impl<'a, T: 'a> FixedDepthVisitor for Vistr<'a, T, PreOrder> {}
 impl<'a, T: 'a> FixedDepthVisitor for Vistr<'a, T, InOrder> {}
 impl<'a, T: 'a> FixedDepthVisitor for Vistr<'a, T, PostOrder> {}
 
+impl<'a, T: 'a> BorrowableVisitor for Vistr<'a, T, PreOrder> {
+    type Reborrow<'b>
+        = Vistr<'b, T, PreOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow()
+    }
+}
+impl<'a, T: 'a> BorrowableVisitor for Vistr<'a, T, InOrder> {
+    type Reborrow<'b>
+        = Vistr<'b, T, InOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow()
+    }
+}
+impl<'a, T: 'a> BorrowableVisitor for Vistr<'a, T, PostOrder> {
+    type Reborrow<'b>
+        = Vistr<'b, T, PostOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow()
+    }
+}
+
+//The allocation shared by all the visitors of one IntoVistr tree.
+//It only frees the memory. The elements are owned and dropped by the visitors.
+struct IntoVistrBuffer<T> {
+    ptr: *mut T,
+    cap: usize,
+}
+
+impl<T> Drop for IntoVistrBuffer<T> {
+    fn drop(&mut self) {
+        //Safety: ptr and cap came from a Vec, and a length of zero drops no elements.
+        unsafe { drop(Vec::from_raw_parts(self.ptr, 0, self.cap)) }
+    }
+}
+
+///Tree visitor that owns its elements and yields them by value.
+///The visitors of all the subtrees share one allocation, and each one owns a disjoint range of it.
+///Elements that were never yielded are dropped along with the visitor that owns them.
+pub struct IntoVistr<T, D> {
+    _p: PhantomData<D>,
+    buffer: Arc<IntoVistrBuffer<T>>,
+    range: Range<usize>,
+}
+
+//Safety: each visitor only ever touches the elements in its own range,
+//so moving it to another thread moves only those elements.
+unsafe impl<T: Send, D: Send> Send for IntoVistr<T, D> {}
+//Safety: a shared reference to a visitor gives no access to its elements.
+unsafe impl<T, D: Sync> Sync for IntoVistr<T, D> {}
+
+impl<T, D> Drop for IntoVistr<T, D> {
+    fn drop(&mut self) {
+        //Safety: the elements in the range are owned by this visitor and have not been yielded.
+        unsafe {
+            let start = self.buffer.ptr.add(self.range.start);
+            core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(start, self.range.len()));
+        }
+    }
+}
+
+impl<T, D> IntoVistr<T, D> {
+    ///Returns the number of nodes left in this subtree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+}
+
+impl<T> Visitor for IntoVistr<T, PreOrder> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        into_vistr_next(self)
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        into_vistr_level_remaining_hint(self)
+    }
+}
+impl<T> Visitor for IntoVistr<T, InOrder> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        into_vistr_next(self)
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        into_vistr_level_remaining_hint(self)
+    }
+}
+impl<T> Visitor for IntoVistr<T, PostOrder> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        into_vistr_next(self)
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        into_vistr_level_remaining_hint(self)
+    }
+}
+
+impl<T> FixedDepthVisitor for IntoVistr<T, PreOrder> {}
+impl<T> FixedDepthVisitor for IntoVistr<T, InOrder> {}
+impl<T> FixedDepthVisitor for IntoVistr<T, PostOrder> {}
+
+fn into_vistr_level_remaining_hint<T, D>(vistr: &IntoVistr<T, D>) -> (usize, Option<usize>) {
+    let left = log_2(vistr.range.len() + 1);
+    (left, Some(left))
+}
+fn into_vistr_next<T, D: DfsOrder>(
+    mut vistr: IntoVistr<T, D>,
+) -> (T, Option<[IntoVistr<T, D>; 2]>) {
+    //Take the range so that dropping vistr no longer drops any elements.
+    let range = core::mem::replace(&mut vistr.range, 0..0);
+    //Safety: index is in the range this visitor owned, and is only read once.
+    let take = |index: usize| unsafe { core::ptr::read(vistr.buffer.ptr.add(index)) };
+    if range.len() == 1 {
+        (take(range.start), None)
+    } else {
+        let (middle, left, right) = D::split_range(range);
+
+        (
+            take(middle),
+            Some([
+                IntoVistr {
+                    _p: PhantomData,
+                    buffer: vistr.buffer.clone(),
+                    range: left,
+                },
+                IntoVistr {
+                    _p: PhantomData,
+                    buffer: vistr.buffer.clone(),
+                    range: right,
+                },
+            ]),
+        )
+    }
+}
+
 impl<'a, T: 'a, D> From<VistrMut<'a, T, D>> for Vistr<'a, T, D> {
     #[inline]
     fn from(a: VistrMut<'a, T, D>) -> Vistr<'a, T, D> {
@@ -440,7 +985,7 @@ pub struct VistrMut<'a, T: 'a, D> {
 
 impl<'a, T: 'a, D> VistrMut<'a, T, D> {
     #[inline]
-    pub fn borrow(&self) -> Vistr<T, D> {
+    pub fn borrow(&self) -> Vistr<'_, T, D> {
         Vistr {
             _p: PhantomData,
             remaining: self.remaining,
@@ -448,7 +993,7 @@ impl<'a, T: 'a, D> VistrMut<'a, T, D> {
     }
 
     #[inline]
-    pub fn borrow_mut(&mut self) -> VistrMut<T, D> {
+    pub fn borrow_mut(&mut self) -> VistrMut<'_, T, D> {
         VistrMut {
             _p: PhantomData,
             remaining: self.remaining,
@@ -459,6 +1004,32 @@ impl<'a, T: 'a, D> VistrMut<'a, T, D> {
     pub fn into_slice(self) -> &'a mut [T] {
         self.remaining
     }
+
+    ///Iterates over the elements in the order they are laid out, which is the dfs order D.
+    ///This iterates the slice directly.
+    #[inline]
+    pub fn iter_layout_mut(self) -> core::slice::IterMut<'a, T> {
+        self.remaining.iter_mut()
+    }
+
+    ///Returns the number of nodes in the tree below and including this visitor.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    ///A visitor always points to at least one node, so this always returns false.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    ///Returns the number of nodes in the left and right subtrees, or None if this is a leaf.
+    ///Since the tree is complete, the two are always equal.
+    #[inline]
+    pub fn child_sizes(&self) -> Option<(usize, usize)> {
+        child_sizes(self.remaining.len())
+    }
 }
 
 fn vistr_mut_dfs_level_remaining_hint<T, D: DfsOrder>(
@@ -468,7 +1039,9 @@ fn vistr_mut_dfs_level_remaining_hint<T, D: DfsOrder>(
     //let left = ((vistr.remaining.len() + 1) as f64).log2() as usize;
     (left, Some(left))
 }
-fn vistr_mut_next<T, D: DfsOrder>(vistr: VistrMut<T, D>) -> (&mut T, Option<[VistrMut<T, D>; 2]>) {
+fn vistr_mut_next<T, D: DfsOrder>(
+    vistr: VistrMut<'_, T, D>,
+) -> (&mut T, Option<[VistrMut<'_, T, D>; 2]>) {
     let remaining = vistr.remaining;
     if remaining.len() == 1 {
         (&mut remaining[0], None)
@@ -559,3 +1132,58 @@ impl<'a, T: 'a> Visitor for VistrMut<'a, T, PostOrder> {
 impl<'a, T: 'a> FixedDepthVisitor for VistrMut<'a, T, PreOrder> {}
 impl<'a, T: 'a> FixedDepthVisitor for VistrMut<'a, T, InOrder> {}
 impl<'a, T: 'a> FixedDepthVisitor for VistrMut<'a, T, PostOrder> {}
+
+impl<'a, T: 'a> BorrowableVisitor for VistrMut<'a, T, PreOrder> {
+    type Reborrow<'b>
+        = VistrMut<'b, T, PreOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow_mut()
+    }
+}
+impl<'a, T: 'a> BorrowableVisitor for VistrMut<'a, T, InOrder> {
+    type Reborrow<'b>
+        = VistrMut<'b, T, InOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow_mut()
+    }
+}
+impl<'a, T: 'a> BorrowableVisitor for VistrMut<'a, T, PostOrder> {
+    type Reborrow<'b>
+        = VistrMut<'b, T, PostOrder>
+    where
+        Self: 'b;
+    #[inline]
+    fn reborrow(&mut self) -> Self::Reborrow<'_> {
+        self.borrow_mut()
+    }
+}
+impl<'a, T: 'a> ByRefVisitor for VistrMut<'a, T, PreOrder> {
+    type ByRef = Vistr<'a, T, PreOrder>;
+    ///Unlike borrow(), the result keeps the full lifetime 'a.
+    #[inline]
+    fn by_ref_items(self) -> Self::ByRef {
+        self.into()
+    }
+}
+impl<'a, T: 'a> ByRefVisitor for VistrMut<'a, T, InOrder> {
+    type ByRef = Vistr<'a, T, InOrder>;
+    ///Unlike borrow(), the result keeps the full lifetime 'a.
+    #[inline]
+    fn by_ref_items(self) -> Self::ByRef {
+        self.into()
+    }
+}
+impl<'a, T: 'a> ByRefVisitor for VistrMut<'a, T, PostOrder> {
+    type ByRef = Vistr<'a, T, PostOrder>;
+    ///Unlike borrow(), the result keeps the full lifetime 'a.
+    #[inline]
+    fn by_ref_items(self) -> Self::ByRef {
+        self.into()
+    }
+}
@@ -45,27 +45,76 @@
 
 #![no_std]
 extern crate alloc;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::mem::MaybeUninit;
 
 ///A complete binary tree stored in a Vec<T> laid out in dfs in order.
 ///One advantage of using the dfs order over the bfs order, is that at any point during traversal of the tree,
 ///you can turn the visitor into a slice representing the rest of the nodes underneath that visitor.
 pub mod dfs_order;
 
-//use core::collections::vec_deque::VecDeque;
-
 ///Compute the number of nodes in a complete binary tree based on a height.
+///The height must be less than the number of bits in a usize.
 #[inline]
 pub fn compute_num_nodes(height: usize) -> usize {
+    debug_assert!(
+        height < usize::BITS as usize,
+        "height {} is too large",
+        height
+    );
     (1 << height) - 1
 }
 
+///Returns the adjacency list of a complete binary tree of the given height.
+///There is one entry per node in bfs order of the form (node index, left child index, right child index).
+///Indices are bfs indices. Leafs have no children.
+///The height of a FixedDepthVisitor can be found with get_height().
+pub fn adjacency_list(height: usize) -> Vec<(usize, Option<usize>, Option<usize>)> {
+    let num = compute_num_nodes(height);
+    let internal = num / 2;
+    (0..num)
+        .map(|i| {
+            if i < internal {
+                (i, Some(2 * i + 1), Some(2 * i + 2))
+            } else {
+                (i, None, None)
+            }
+        })
+        .collect()
+}
+
+///Compute the number of nodes in a complete binary tree based on a height.
+///Returns None if the number of nodes does not fit in a usize.
+#[inline]
+pub fn checked_num_nodes(height: usize) -> Option<usize> {
+    match height.cmp(&(usize::BITS as usize)) {
+        Ordering::Less => Some((1 << height) - 1),
+        Ordering::Equal => Some(usize::MAX),
+        Ordering::Greater => None,
+    }
+}
+
 ///Error indicating the vec that was passed is not a size that you would expect for the given height.
 #[derive(Copy, Clone, Debug)]
 pub struct NotCompleteTreeSizeErr {
     pub length: usize,
 }
 
+///Error indicating two visitors that were expected to be the same height are not.
+///Contains the heights of both.
+#[derive(Copy, Clone, Debug)]
+pub struct ShapeMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
 fn valid_node_num(num: usize) -> Result<(), NotCompleteTreeSizeErr> {
     if (num + 1).is_power_of_two() && num != 0 {
         Ok(())
@@ -77,11 +126,28 @@ fn valid_node_num(num: usize) -> Result<(), NotCompleteTreeSizeErr> {
 ///Computes the height for the number of nodes given.
 ///Returns the number of trailing zeroes after the last bit in the binary representation.
 ///For complete binary trees this would be the height.
+///The number of nodes must be a complete tree size.
 #[inline]
 pub fn compute_height(num_nodes: usize) -> usize {
+    debug_assert!(
+        checked_height(num_nodes).is_some(),
+        "{} is not a complete tree size",
+        num_nodes
+    );
     (num_nodes + 1).trailing_zeros() as usize
 }
 
+///Computes the height for the number of nodes given.
+///Returns None if the number of nodes is not 2^n-1 for some n.
+#[inline]
+pub fn checked_height(num_nodes: usize) -> Option<usize> {
+    match num_nodes.checked_add(1) {
+        Some(a) if a.is_power_of_two() => Some(a.trailing_zeros() as usize),
+        Some(_) => None,
+        None => Some(usize::BITS as usize),
+    }
+}
+
 ///Dfs in order iterator. Each call to next() will return the next element
 ///in dfs in order.
 ///Internally uses a Vec for the stack.
@@ -119,68 +185,518 @@ impl<C: Visitor> Iterator for DfsInOrderIter<C> {
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         match self.a.pop() {
-            Some((i, nl)) => match nl {
-                Some(nl) => {
-                    let res = i;
+            Some((i, nl)) => {
+                if let Some(nl) = nl {
                     DfsInOrderIter::add_all_lefts(&mut self.a, nl);
-                    self.num += 1;
-                    Some(res)
                 }
-                None => Some(i),
-            },
+                self.num += 1;
+                Some(i)
+            }
             None => None,
         }
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         (
-            self.min_length - self.num,
-            self.length.map(|a| a - self.num),
+            self.min_length.saturating_sub(self.num),
+            self.length.map(|a| a.saturating_sub(self.num)),
         )
     }
 }
 
 impl<C: Visitor> core::iter::FusedIterator for DfsInOrderIter<C> {}
-impl<C: FixedDepthVisitor> core::iter::ExactSizeIterator for DfsInOrderIter<C> {}
+impl<C: FixedDepthVisitor> core::iter::ExactSizeIterator for DfsInOrderIter<C> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.min_length - self.num
+    }
+}
+
+///Reverse dfs in order iterator. Each call to next() will return the next element
+///in reverse dfs in order (right,root,left).
+///Internally uses a Vec for the stack.
+#[derive(Clone)]
+pub struct DfsInOrderRevIter<C: Visitor> {
+    a: Vec<(C::Item, Option<C>)>,
+    length: Option<usize>,
+    min_length: usize,
+    num: usize,
+}
+
+impl<C: Visitor> DfsInOrderRevIter<C> {
+    fn add_all_rights(stack: &mut Vec<(C::Item, Option<C>)>, node: C) {
+        let mut target = Some(node);
+        loop {
+            let (i, next) = target.take().unwrap().next();
+            match next {
+                Some([left, right]) => {
+                    let bleep = (i, Some(left));
+                    stack.push(bleep);
+                    target = Some(right);
+                }
+                None => {
+                    let bleep = (i, None);
+                    stack.push(bleep);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl<C: Visitor> Iterator for DfsInOrderRevIter<C> {
+    type Item = C::Item;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.a.pop() {
+            Some((i, nl)) => {
+                if let Some(nl) = nl {
+                    DfsInOrderRevIter::add_all_rights(&mut self.a, nl);
+                }
+                self.num += 1;
+                Some(i)
+            }
+            None => None,
+        }
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (
+            self.min_length.saturating_sub(self.num),
+            self.length.map(|a| a.saturating_sub(self.num)),
+        )
+    }
+}
+
+impl<C: Visitor> core::iter::FusedIterator for DfsInOrderRevIter<C> {}
+impl<C: FixedDepthVisitor> core::iter::ExactSizeIterator for DfsInOrderRevIter<C> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.min_length - self.num
+    }
+}
 
 ///Dfs preorder iterator. Each call to next() will return the next element
 ///in dfs order.
 ///Internally uses a Vec for the stack.
-#[derive(Clone)]
 pub struct DfsPreOrderIter<C: Visitor> {
     a: Vec<C>,
+    peeked: Option<C::Item>,
     length: Option<usize>,
     min_length: usize,
     num: usize,
 }
 
+///The items must be Clone too, since peek() may have already taken the next one from the visitor.
+impl<C: Visitor + Clone> Clone for DfsPreOrderIter<C>
+where
+    C::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        DfsPreOrderIter {
+            a: self.a.clone(),
+            peeked: self.peeked.clone(),
+            length: self.length,
+            min_length: self.min_length,
+            num: self.num,
+        }
+    }
+}
+
+impl<C: Visitor> DfsPreOrderIter<C> {
+    ///Returns a reference to the next element without consuming it.
+    #[inline]
+    pub fn peek(&mut self) -> Option<&C::Item> {
+        if self.peeked.is_none() {
+            self.peeked = self.advance();
+        }
+        self.peeked.as_ref()
+    }
+
+    fn advance(&mut self) -> Option<C::Item> {
+        let (i, next) = self.a.pop()?.next();
+        if let Some([left, right]) = next {
+            self.a.push(right);
+            self.a.push(left);
+        }
+        Some(i)
+    }
+}
+
 impl<C: Visitor> core::iter::FusedIterator for DfsPreOrderIter<C> {}
-impl<C: FixedDepthVisitor> core::iter::ExactSizeIterator for DfsPreOrderIter<C> {}
+impl<C: FixedDepthVisitor> core::iter::ExactSizeIterator for DfsPreOrderIter<C> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.min_length - self.num
+    }
+}
 
 impl<C: Visitor> Iterator for DfsPreOrderIter<C> {
     type Item = C::Item;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        match self.a.pop() {
-            Some(x) => {
-                let (i, next) = x.next();
+        let i = match self.peeked.take() {
+            Some(i) => i,
+            None => self.advance()?,
+        };
+        self.num += 1;
+        if let Some(length) = self.length {
+            debug_assert!(
+                self.num <= length,
+                "visitor produced more nodes than its level_remaining_hint() allows"
+            );
+        }
+        Some(i)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (
+            self.min_length.saturating_sub(self.num),
+            self.length.map(|a| a.saturating_sub(self.num)),
+        )
+    }
+}
+
+///Iterator that skips the nodes in the top levels of a tree.
+///Produced by Visitor::skip_levels(). Elements are returned in dfs preorder.
+pub struct SkipLevels<C: Visitor> {
+    inner: DfsPreOrderIter<LevelIter<C>>,
+    num: usize,
+}
+
+impl<C: Visitor> Clone for SkipLevels<C>
+where
+    DfsPreOrderIter<LevelIter<C>>: Clone,
+{
+    fn clone(&self) -> Self {
+        SkipLevels {
+            inner: self.inner.clone(),
+            num: self.num,
+        }
+    }
+}
+
+impl<C: Visitor> core::iter::FusedIterator for SkipLevels<C> {}
+
+impl<C: Visitor> Iterator for SkipLevels<C> {
+    type Item = C::Item;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let num = self.num;
+        self.inner
+            .by_ref()
+            .find(|(depth, _)| depth.0 >= num)
+            .map(|(_, a)| a)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+///Iterator that only returns the nodes at every kth level, along with their depth.
+///Produced by Visitor::every_k_levels(). Elements are returned in dfs preorder.
+pub struct EveryKLevels<C: Visitor> {
+    inner: DfsPreOrderIter<LevelIter<C>>,
+    k: usize,
+}
+
+impl<C: Visitor> Clone for EveryKLevels<C>
+where
+    DfsPreOrderIter<LevelIter<C>>: Clone,
+{
+    fn clone(&self) -> Self {
+        EveryKLevels {
+            inner: self.inner.clone(),
+            k: self.k,
+        }
+    }
+}
+
+impl<C: Visitor> core::iter::FusedIterator for EveryKLevels<C> {}
+
+impl<C: Visitor> Iterator for EveryKLevels<C> {
+    type Item = (Depth, C::Item);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let k = self.k;
+        self.inner.by_ref().find(|(depth, _)| depth.0 % k == 0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+///Iterator that returns all the elements the closure produces for each node, in dfs preorder.
+///Produced by Visitor::expand_by_level().
+pub struct ExpandByLevel<C: Visitor, B, F> {
+    inner: DfsPreOrderIter<LevelIter<C>>,
+    func: F,
+    current: alloc::vec::IntoIter<B>,
+}
+
+impl<C: Visitor, B, F: Fn(Depth, C::Item) -> Vec<B>> core::iter::FusedIterator
+    for ExpandByLevel<C, B, F>
+{
+}
+
+impl<C: Visitor, B, F: Fn(Depth, C::Item) -> Vec<B>> Iterator for ExpandByLevel<C, B, F> {
+    type Item = B;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(b) = self.current.next() {
+                return Some(b);
+            }
+            let (depth, a) = self.inner.next()?;
+            self.current = (self.func)(depth, a).into_iter();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        //Once there are no nodes left, only the elements already produced remain.
+        let current = self.current.len();
+        match self.inner.size_hint().1 {
+            Some(0) => (current, Some(current)),
+            _ => (current, None),
+        }
+    }
+}
+
+///Iterator that alternates between the dfs preorder elements of two trees.
+///Produced by Visitor::interleave(). Once one tree runs out, the rest of the other tree is returned.
+pub struct Interleave<A: Visitor, B: Visitor<Item = A::Item>> {
+    a: DfsPreOrderIter<A>,
+    b: DfsPreOrderIter<B>,
+    a_next: bool,
+}
+
+impl<A: Visitor, B: Visitor<Item = A::Item>> Clone for Interleave<A, B>
+where
+    DfsPreOrderIter<A>: Clone,
+    DfsPreOrderIter<B>: Clone,
+{
+    fn clone(&self) -> Self {
+        Interleave {
+            a: self.a.clone(),
+            b: self.b.clone(),
+            a_next: self.a_next,
+        }
+    }
+}
+
+impl<A: Visitor, B: Visitor<Item = A::Item>> core::iter::FusedIterator for Interleave<A, B> {}
+
+impl<A: Visitor, B: Visitor<Item = A::Item>> Iterator for Interleave<A, B> {
+    type Item = A::Item;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let a_next = self.a_next;
+        self.a_next = !a_next;
+        if a_next {
+            self.a.next().or_else(|| self.b.next())
+        } else {
+            self.b.next().or_else(|| self.a.next())
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_min, a_max) = self.a.size_hint();
+        let (b_min, b_max) = self.b.size_hint();
+        let max = match (a_max, b_max) {
+            (Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        };
+        (a_min + b_min, max)
+    }
+}
+
+///Iterator that returns the elements on the path from the root to each leaf.
+///Produced by Visitor::root_to_leaf_paths(). Leafs are visited from left to right.
+pub struct RootToLeafPaths<C: Visitor> {
+    a: Vec<(C, Vec<C::Item>)>,
+}
+
+impl<C: Visitor> core::iter::FusedIterator for RootToLeafPaths<C> where C::Item: Clone {}
+
+impl<C: Visitor> Iterator for RootToLeafPaths<C>
+where
+    C::Item: Clone,
+{
+    type Item = Vec<C::Item>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut target, mut path) = self.a.pop()?;
+        loop {
+            let (i, next) = target.next();
+            path.push(i);
+            match next {
+                Some([left, right]) => {
+                    self.a.push((right, path.clone()));
+                    target = left;
+                }
+                None => return Some(path),
+            }
+        }
+    }
+}
+
+///Iterator that returns a (parent, child) pair for every edge in the tree.
+///Produced by Visitor::edges(). The pairs are returned in dfs preorder of the child.
+pub struct Edges<C: Visitor> {
+    a: Vec<(C::Item, C)>,
+}
+
+impl<C: Visitor> Edges<C>
+where
+    C::Item: Clone,
+{
+    #[inline]
+    fn push_children(&mut self, parent: C::Item, rest: Option<[C; 2]>) {
+        if let Some([left, right]) = rest {
+            self.a.push((parent.clone(), right));
+            self.a.push((parent, left));
+        }
+    }
+}
+
+impl<C: Visitor> core::iter::FusedIterator for Edges<C> where C::Item: Clone {}
+
+impl<C: Visitor> Iterator for Edges<C>
+where
+    C::Item: Clone,
+{
+    type Item = (C::Item, C::Item);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (parent, target) = self.a.pop()?;
+        let (i, rest) = target.next();
+        self.push_children(i.clone(), rest);
+        Some((parent, i))
+    }
+}
+
+///Iterator over the nodes that differ between two trees.
+///Produced by Visitor::diff(). Nodes are returned in dfs preorder.
+pub struct Diff<A: Visitor, B: Visitor<Item = A::Item>> {
+    a: Vec<(Vec<Branch>, A, B)>,
+}
+
+impl<A: Visitor, B: Visitor<Item = A::Item>> core::iter::FusedIterator for Diff<A, B> where
+    A::Item: PartialEq
+{
+}
+
+impl<A: Visitor, B: Visitor<Item = A::Item>> Iterator for Diff<A, B>
+where
+    A::Item: PartialEq,
+{
+    type Item = (Vec<Branch>, A::Item, A::Item);
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, a, b) = self.a.pop()?;
+            let (a, arest) = a.next();
+            let (b, brest) = b.next();
+
+            let diverged = match (arest, brest) {
+                (Some([al, ar]), Some([bl, br])) => {
+                    let mut right = path.clone();
+                    right.push(Branch::Right);
+                    self.a.push((right, ar, br));
+                    let mut left = path.clone();
+                    left.push(Branch::Left);
+                    self.a.push((left, al, bl));
+                    false
+                }
+                (None, None) => false,
+                _ => true,
+            };
+
+            if diverged || a != b {
+                return Some((path, a, b));
+            }
+        }
+    }
+}
+
+///Specifies the order of a traversal chosen at runtime. See Visitor::iter_ordered().
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraversalOrder {
+    PreOrder,
+    InOrder,
+    PostOrder,
+    BreadthFirst,
+}
+
+///Iterator over a tree in a TraversalOrder chosen at runtime.
+///Produced by Visitor::iter_ordered().
+pub struct OrderedIter<C: Visitor> {
+    inner: OrderedIterInner<C>,
+}
+
+enum OrderedIterInner<C: Visitor> {
+    PreOrder(DfsPreOrderIter<C>),
+    InOrder(DfsInOrderIter<C>),
+    PostOrder(Vec<PostOrderEntry<C>>),
+    BreadthFirst(VecDeque<C>),
+}
+
+enum PostOrderEntry<C: Visitor> {
+    Expand(C),
+    Emit(C::Item),
+}
+
+impl<C: Visitor> core::iter::FusedIterator for OrderedIter<C> {}
+
+impl<C: Visitor> Iterator for OrderedIter<C> {
+    type Item = C::Item;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            OrderedIterInner::PreOrder(a) => a.next(),
+            OrderedIterInner::InOrder(a) => a.next(),
+            OrderedIterInner::PostOrder(stack) => loop {
+                match stack.pop()? {
+                    PostOrderEntry::Emit(i) => return Some(i),
+                    PostOrderEntry::Expand(x) => {
+                        let (i, next) = x.next();
+                        match next {
+                            Some([left, right]) => {
+                                stack.push(PostOrderEntry::Emit(i));
+                                stack.push(PostOrderEntry::Expand(right));
+                                stack.push(PostOrderEntry::Expand(left));
+                            }
+                            None => return Some(i),
+                        }
+                    }
+                }
+            },
+            OrderedIterInner::BreadthFirst(queue) => {
+                let (i, next) = queue.pop_front()?.next();
                 if let Some([left, right]) = next {
-                    self.a.push(right);
-                    self.a.push(left);
+                    queue.push_back(left);
+                    queue.push_back(right);
                 }
-                self.num += 1;
                 Some(i)
             }
-            None => None,
         }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (
-            self.min_length - self.num,
-            self.length.map(|a| a - self.num),
-        )
+        match &self.inner {
+            OrderedIterInner::PreOrder(a) => a.size_hint(),
+            OrderedIterInner::InOrder(a) => a.size_hint(),
+            OrderedIterInner::PostOrder(stack) => (stack.len(), None),
+            OrderedIterInner::BreadthFirst(queue) => (queue.len(), None),
+        }
     }
 }
 
@@ -222,8 +738,8 @@ impl<C: Visitor> Iterator for BfsIter<C> {
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         (
-            self.min_length - self.num,
-            self.length.map(|a| a - self.num),
+            self.min_length.saturating_sub(self.num),
+            self.length.map(|a| a.saturating_sub(self.num)),
         )
     }
 }
@@ -258,24 +774,264 @@ impl<B, C: Visitor, F: Fn(C::Item) -> B + Clone> Visitor for Map<C, F> {
             None => (k, None),
         }
     }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
 }
 
 impl<B, C: FixedDepthVisitor, F: Fn(C::Item) -> B + Clone> FixedDepthVisitor for Map<C, F> {}
 
-///If implemented, then the level_remaining_hint must return the exact height of the tree.
-///If this is implemented, then the exact number of nodes that will be returned by a dfs or bfs traversal is known
-///so those iterators can implement TrustedLen in this case.
-pub trait FixedDepthVisitor: Visitor {
-    fn get_height(&self) -> usize {
-        self.level_remaining_hint().0
-    }
+///Map iterator adapter that also passes the bfs index of each element.
+#[derive(Clone)]
+pub struct MapIndex<C, F> {
+    func: F,
+    inner: C,
+    index: usize,
 }
+impl<B, C: Visitor, F: Fn(usize, C::Item) -> B + Clone> Visitor for MapIndex<C, F> {
+    type Item = B;
 
-use core::iter::FusedIterator;
-///A version of iterating in dfs preorder implemented using iter::from_fn
-///TODO implement all the iterators with this.
-pub fn dfs_preorder_iter2<C: Visitor>(a: C) -> impl Iterator<Item = C::Item> + FusedIterator {
-    let mut stack = Vec::new();
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.inner.next();
+
+        let k = (self.func)(self.index, a);
+        match rest {
+            Some([left, right]) => {
+                let ll = MapIndex {
+                    func: self.func.clone(),
+                    inner: left,
+                    index: 2 * self.index + 1,
+                };
+                let rr = MapIndex {
+                    func: self.func,
+                    inner: right,
+                    index: 2 * self.index + 2,
+                };
+                (k, Some([ll, rr]))
+            }
+            None => (k, None),
+        }
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
+}
+
+impl<B, C: FixedDepthVisitor, F: Fn(usize, C::Item) -> B + Clone> FixedDepthVisitor
+    for MapIndex<C, F>
+{
+}
+
+///Map iterator adapter that also keeps the original element.
+#[derive(Clone)]
+pub struct MapKeyed<C, F> {
+    func: F,
+    inner: C,
+}
+impl<B, C: Visitor, F: Fn(&C::Item) -> B + Clone> Visitor for MapKeyed<C, F> {
+    type Item = (C::Item, B);
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.inner.next();
+
+        let b = (self.func)(&a);
+        match rest {
+            Some([left, right]) => {
+                let ll = MapKeyed {
+                    func: self.func.clone(),
+                    inner: left,
+                };
+                let rr = MapKeyed {
+                    func: self.func,
+                    inner: right,
+                };
+                ((a, b), Some([ll, rr]))
+            }
+            None => ((a, b), None),
+        }
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
+}
+
+impl<B, C: FixedDepthVisitor, F: Fn(&C::Item) -> B + Clone> FixedDepthVisitor for MapKeyed<C, F> {}
+
+///Map iterator adapter that only maps the leaf elements.
+#[derive(Clone)]
+pub struct MapLeaves<C, F> {
+    func: F,
+    inner: C,
+}
+impl<C: Visitor, F: Fn(C::Item) -> C::Item + Clone> Visitor for MapLeaves<C, F> {
+    type Item = C::Item;
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.inner.next();
+
+        match rest {
+            Some([left, right]) => {
+                let ll = MapLeaves {
+                    func: self.func.clone(),
+                    inner: left,
+                };
+                let rr = MapLeaves {
+                    func: self.func,
+                    inner: right,
+                };
+                (a, Some([ll, rr]))
+            }
+            None => ((self.func)(a), None),
+        }
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
+}
+
+impl<C: FixedDepthVisitor, F: Fn(C::Item) -> C::Item + Clone> FixedDepthVisitor
+    for MapLeaves<C, F>
+{
+}
+
+///Map iterator adapter that threads a state through every call of the closure.
+///The state is shared by all the visitors produced from this one, so it is passed along
+///in the order next() is called on them. Cloning this visitor does not clone the state.
+///Since the state is behind an Rc, this is not Send.
+pub struct MapStateful<C, S, F> {
+    inner: C,
+    state: Rc<RefCell<(S, F)>>,
+}
+impl<C: Clone, S, F> Clone for MapStateful<C, S, F> {
+    fn clone(&self) -> Self {
+        MapStateful {
+            inner: self.inner.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+impl<B, C: Visitor, S, F: FnMut(&mut S, C::Item) -> B> Visitor for MapStateful<C, S, F> {
+    type Item = B;
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.inner.next();
+
+        let k = {
+            let mut state = self.state.borrow_mut();
+            let (state, func) = &mut *state;
+            func(state, a)
+        };
+        match rest {
+            Some([left, right]) => {
+                let ll = MapStateful {
+                    inner: left,
+                    state: self.state.clone(),
+                };
+                let rr = MapStateful {
+                    inner: right,
+                    state: self.state,
+                };
+                (k, Some([ll, rr]))
+            }
+            None => (k, None),
+        }
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
+}
+
+impl<B, C: FixedDepthVisitor, S, F: FnMut(&mut S, C::Item) -> B> FixedDepthVisitor
+    for MapStateful<C, S, F>
+{
+}
+
+///If implemented, then the level_remaining_hint must return the exact height of the tree.
+///If this is implemented, then the exact number of nodes that will be returned by a dfs or bfs traversal is known
+///so those iterators can implement TrustedLen in this case.
+pub trait FixedDepthVisitor: Visitor {
+    fn get_height(&self) -> usize {
+        self.level_remaining_hint().0
+    }
+}
+
+///A boxed visitor trait object. Produced by Visitor::into_dyn().
+pub type BoxedVisitor<'a, T> = Box<dyn DynVisitor<'a, Item = T> + 'a>;
+
+///Object safe version of Visitor. Implemented by every Visitor.
+///See Visitor::into_dyn().
+pub trait DynVisitor<'a> {
+    type Item;
+    #[allow(clippy::type_complexity)]
+    fn next_dyn(self: Box<Self>) -> (Self::Item, Option<[BoxedVisitor<'a, Self::Item>; 2]>);
+    fn level_remaining_hint_dyn(&self) -> (usize, Option<usize>);
+}
+
+impl<'a, V: Visitor + 'a> DynVisitor<'a> for V {
+    type Item = V::Item;
+    #[inline]
+    fn next_dyn(self: Box<Self>) -> (Self::Item, Option<[BoxedVisitor<'a, Self::Item>; 2]>) {
+        let (a, rest) = (*self).next();
+        (
+            a,
+            rest.map(|[left, right]| [left.into_dyn(), right.into_dyn()]),
+        )
+    }
+
+    #[inline]
+    fn level_remaining_hint_dyn(&self) -> (usize, Option<usize>) {
+        self.level_remaining_hint()
+    }
+}
+
+impl<'a, T> Visitor for BoxedVisitor<'a, T> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        self.next_dyn()
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        (**self).level_remaining_hint_dyn()
+    }
+}
+
+///A visitor that can lend out a shorter lived copy of itself.
+///This lets generic code traverse a tree without consuming the original visitor.
+pub trait BorrowableVisitor: Visitor {
+    type Reborrow<'b>: Visitor
+    where
+        Self: 'b;
+    fn reborrow(&mut self) -> Self::Reborrow<'_>;
+}
+
+///A visitor of mutable references that can be turned into a visitor of shared references
+///to the same elements. Unlike reborrowing, the result keeps the full lifetime of the elements.
+pub trait ByRefVisitor: Visitor {
+    type ByRef: Visitor;
+    fn by_ref_items(self) -> Self::ByRef;
+}
+
+use core::iter::FusedIterator;
+///A version of iterating in dfs preorder implemented using iter::from_fn
+///TODO implement all the iterators with this.
+pub fn dfs_preorder_iter2<C: Visitor>(a: C) -> impl FusedIterator<Item = C::Item> {
+    let mut stack = Vec::new();
     stack.push(a);
     core::iter::from_fn(move || {
         if let Some(x) = stack.pop() {
@@ -294,6 +1050,7 @@ pub fn dfs_preorder_iter2<C: Visitor>(a: C) -> impl Iterator<Item = C::Item> + F
 
 ///The trait this crate revoles around.
 ///A complete binary tree visitor.
+///Every node has either zero or two children. Trees with any other fan out can not be represented.
 pub trait Visitor: Sized {
     ///The common item produced for both leafs and non leafs.
     type Item;
@@ -327,24 +1084,203 @@ pub trait Visitor: Sized {
         Zip { a: self, b: f }
     }
 
+    ///Combine two tree visitors, returning an error instead of silently stopping at the shorter tree
+    ///if the upper bounds of their level_remaining_hint() are both known and differ.
+    #[inline]
+    fn try_zip<F: Visitor>(self, f: F) -> Result<Zip<Self, F>, ShapeMismatch> {
+        match (self.level_remaining_hint().1, f.level_remaining_hint().1) {
+            (Some(left), Some(right)) if left != right => Err(ShapeMismatch { left, right }),
+            _ => Ok(self.zip(f)),
+        }
+    }
+
+    ///Assigns successive values from the iterator to the elements in dfs preorder.
+    ///Panics if the iterator runs out before every element is assigned.
+    #[inline]
+    fn fill_preorder<'a, T: 'a>(self, values: impl IntoIterator<Item = T>)
+    where
+        Self: Visitor<Item = &'a mut T>,
+    {
+        let mut values = values.into_iter();
+        self.dfs_preorder(|a| *a = values.next().expect("not enough values to fill the tree"));
+    }
+
+    ///Zips this tree of mutable references with another tree,
+    ///calling the closure on each pair so that the other tree's elements can be merged in.
+    ///Stops at the shorter of the two trees, just like zip().
+    #[inline]
+    fn merge_into<'a, T: 'a, F: Visitor>(self, other: F, mut func: impl FnMut(&mut T, F::Item))
+    where
+        Self: Visitor<Item = &'a mut T>,
+    {
+        self.zip(other).dfs_preorder(|(a, b)| func(a, b));
+    }
+
+    ///Combine two tree visitors, producing the ordering between each pair of elements.
+    ///Useful for finding where two sorted trees differ.
+    #[inline]
+    fn zip_cmp<F: Visitor<Item = Self::Item>>(self, f: F) -> ZipCmp<Self, F>
+    where
+        Self::Item: Ord,
+    {
+        self.zip(f).map(cmp_pair)
+    }
+
+    ///Returns the visitors for the subtrees rooted at the given depth, from left to right.
+    ///The root is at depth 0. The elements above that depth are dropped.
+    ///For a complete tree there are 2^depth of them. Branches that end before that depth are skipped.
+    #[inline]
+    fn subtrees_at_depth(self, depth: usize) -> alloc::vec::IntoIter<Self> {
+        let mut current = alloc::vec![self];
+        for _ in 0..depth {
+            let mut next = Vec::with_capacity(current.len() * 2);
+            for a in current {
+                if let Some([left, right]) = a.next().1 {
+                    next.push(left);
+                    next.push(right);
+                }
+            }
+            current = next;
+        }
+        current.into_iter()
+    }
+
+    ///Consumes the root and combines its two children. Returns None if the root is a leaf.
+    ///Useful for comparing the left and right subtrees.
+    #[inline]
+    fn zip_children(self) -> Option<Zip<Self, Self>> {
+        let (_, rest) = self.next();
+        rest.map(|[left, right]| left.zip(right))
+    }
+
+    ///Combine two tree visitors, only producing children up to num levels below the root.
+    ///The level_remaining_hint() is the smaller of the two visitors' hints, capped at num+1 levels.
+    #[inline(always)]
+    fn zip_take<F: Visitor>(self, f: F, num: usize) -> Take<Zip<Self, F>> {
+        self.zip(f).take(num)
+    }
+
+    ///Combine two tree visitors, continuing to the deeper of the two.
+    ///Where one tree has run out, None is produced for its side.
+    #[inline(always)]
+    fn zip_pad<F: Visitor>(self, f: F) -> ZipPad<Self, F> {
+        ZipPad {
+            a: Some(self),
+            b: Some(f),
+        }
+    }
+
+    ///Combine two tree visitors and map each pair of elements along with their depth.
+    ///The root is at depth 0. This is zip(), with_depth() and map() in a single adapter,
+    ///with a closure that takes the depth and the two elements as separate arguments.
+    #[inline(always)]
+    fn zip_depth_map<F: Visitor, O, M: Fn(Depth, Self::Item, F::Item) -> O>(
+        self,
+        f: F,
+        func: M,
+    ) -> ZipDepthMap<Self, F, M> {
+        ZipDepthMap {
+            a: self,
+            b: f,
+            depth: 0,
+            func,
+        }
+    }
+
+    ///Combine two tree visitors, also producing the depth of each element.
+    ///The root is at depth 0.
+    #[inline(always)]
+    fn zip_depth<F: Visitor>(self, f: F) -> ZipDepth<Self, F> {
+        ZipDepth {
+            inner: self.zip(f).with_depth(Depth(0)),
+        }
+    }
+
     ///Map iterator adapter
+    ///A closure that captures nothing is zero sized, so Map is then the same size as the visitor it wraps.
     #[inline(always)]
     fn map<B, F: Fn(Self::Item) -> B>(self, func: F) -> Map<Self, F> {
         Map { func, inner: self }
     }
 
+    ///Map iterator adapter that produces the original element along with the mapped one.
+    #[inline(always)]
+    fn map_keyed<B, F: Fn(&Self::Item) -> B>(self, func: F) -> MapKeyed<Self, F> {
+        MapKeyed { func, inner: self }
+    }
+
+    ///Map iterator adapter that also passes the bfs index of each element to the closure.
+    ///The root is at index 0, and the children of index k are at 2k+1 and 2k+2.
+    #[inline(always)]
+    fn map_index<B, F: Fn(usize, Self::Item) -> B>(self, func: F) -> MapIndex<Self, F> {
+        MapIndex {
+            func,
+            inner: self,
+            index: 0,
+        }
+    }
+
+    ///Map iterator adapter that only maps the leaf elements.
+    ///Non leaf elements are produced as they are.
+    #[inline(always)]
+    fn map_leaves<F: Fn(Self::Item) -> Self::Item + Clone>(self, func: F) -> MapLeaves<Self, F> {
+        MapLeaves { func, inner: self }
+    }
+
+    ///Map iterator adapter that also passes a state to the closure.
+    ///The state is passed along in the order next() is called on the visitors.
+    ///All the dfs traversals, including inorder and postorder, do this in dfs preorder,
+    ///so the state flows down the left subtree before the right.
+    ///bfs() and iter_ordered(TraversalOrder::BreadthFirst) call next() in bfs order instead,
+    ///so with them the state flows level by level.
+    ///The state is shared through an Rc, so the visitor and all its children are not Send.
+    ///The children can not be handed to other threads for parallel work.
+    #[inline(always)]
+    fn map_stateful<B, S, F: FnMut(&mut S, Self::Item) -> B>(
+        self,
+        state: S,
+        func: F,
+    ) -> MapStateful<Self, S, F> {
+        MapStateful {
+            inner: self,
+            state: Rc::new(RefCell::new((state, func))),
+        }
+    }
+
     ///Only produce children up to num.
     #[inline(always)]
     fn take(self, num: usize) -> Take<Self> {
         Take { a: self, num }
     }
 
+    ///Maps every element, only producing children down to max_depth. The root is at depth 0.
+    ///This is take() followed by map().
+    #[inline(always)]
+    fn map_until_depth<B, F: Fn(Self::Item) -> B>(
+        self,
+        max_depth: usize,
+        func: F,
+    ) -> Map<Take<Self>, F> {
+        self.take(max_depth).map(func)
+    }
+
     ///Flips left and right children.
     #[inline(always)]
     fn flip(self) -> Flip<Self> {
         Flip(self)
     }
 
+    ///Flips left and right children, but only of the nodes at depths where should_flip returns true.
+    ///The root is at depth 0.
+    #[inline]
+    fn flip_at<F: Fn(Depth) -> bool + Clone>(self, should_flip: F) -> FlipAt<Self, F> {
+        FlipAt {
+            inner: self,
+            depth: 0,
+            should_flip,
+        }
+    }
+
     /*
     ///Provides an iterator that returns each element in bfs order.
     #[inline]
@@ -385,6 +1321,7 @@ pub trait Visitor: Sized {
         let length = max_levels.map(|levels_max| 2usize.pow(levels_max as u32) - 1);
         DfsPreOrderIter {
             a,
+            peeked: None,
             length,
             min_length,
             num: 0,
@@ -409,6 +1346,192 @@ pub trait Visitor: Sized {
         }
     }
 
+    ///Provides a boxed dfs preorder iterator.
+    ///Useful for storing the traversals of visitors of different types together.
+    #[inline]
+    fn dfs_preorder_boxed<'a>(self) -> Box<dyn Iterator<Item = Self::Item> + 'a>
+    where
+        Self: 'a,
+    {
+        Box::new(self.dfs_preorder_iter())
+    }
+
+    ///Boxes this visitor as a trait object.
+    ///Useful for storing visitors of different types together.
+    ///The box itself implements Visitor.
+    #[inline]
+    fn into_dyn<'a>(self) -> BoxedVisitor<'a, Self::Item>
+    where
+        Self: 'a,
+    {
+        Box::new(self)
+    }
+
+    ///Provides an iterator that returns, for each leaf, the elements on the path from the root to it.
+    ///Leafs are visited from left to right.
+    #[inline]
+    fn root_to_leaf_paths(self) -> RootToLeafPaths<Self>
+    where
+        Self::Item: Clone,
+    {
+        let (levels, _) = self.level_remaining_hint();
+        let mut a = Vec::with_capacity(levels);
+        a.push((self, Vec::with_capacity(levels)));
+        RootToLeafPaths { a }
+    }
+
+    ///Provides an iterator over the nodes whose elements differ between this tree and another,
+    ///along with the path to them from the root.
+    ///If only one of two nodes has children, that pair is returned even if the elements are equal,
+    ///and neither of their subtrees is visited.
+    #[inline]
+    fn diff<B: Visitor<Item = Self::Item>>(self, other: B) -> Diff<Self, B>
+    where
+        Self::Item: PartialEq,
+    {
+        let (levels, _) = self.level_remaining_hint();
+        let mut a = Vec::with_capacity(levels);
+        a.push((Vec::new(), self, other));
+        Diff { a }
+    }
+
+    ///Provides an iterator that returns a (parent, child) pair for every edge in the tree.
+    ///The pairs are returned in dfs preorder of the child, so a tree with n nodes has n-1 edges.
+    #[inline]
+    fn edges(self) -> Edges<Self>
+    where
+        Self::Item: Clone,
+    {
+        let (levels, _) = self.level_remaining_hint();
+        let mut edges = Edges {
+            a: Vec::with_capacity(levels * 2),
+        };
+        let (i, rest) = self.next();
+        edges.push_children(i, rest);
+        edges
+    }
+
+    ///Provides a reverse dfs inorder iterator (right,root,left).
+    ///Unlike reversing the result of dfs_inorder_iter(), this does not need to collect the elements first.
+    #[inline]
+    fn dfs_inorder_rev_iter(self) -> DfsInOrderRevIter<Self> {
+        let (levels, max_levels) = self.level_remaining_hint();
+        let mut a = Vec::with_capacity(levels);
+
+        let length = max_levels.map(|levels_max| 2usize.pow(levels_max as u32) - 1);
+
+        let min_length = 2usize.pow(levels as u32) - 1;
+
+        DfsInOrderRevIter::add_all_rights(&mut a, self);
+
+        DfsInOrderRevIter {
+            a,
+            min_length,
+            length,
+            num: 0,
+        }
+    }
+
+    ///Provides an iterator in the given order, which can be chosen at runtime.
+    #[inline]
+    fn iter_ordered(self, order: TraversalOrder) -> OrderedIter<Self> {
+        let inner = match order {
+            TraversalOrder::PreOrder => OrderedIterInner::PreOrder(self.dfs_preorder_iter()),
+            TraversalOrder::InOrder => OrderedIterInner::InOrder(self.dfs_inorder_iter()),
+            TraversalOrder::PostOrder => {
+                let (levels, _) = self.level_remaining_hint();
+                let mut a = Vec::with_capacity(levels * 2);
+                a.push(PostOrderEntry::Expand(self));
+                OrderedIterInner::PostOrder(a)
+            }
+            TraversalOrder::BreadthFirst => {
+                let (levels, _) = self.level_remaining_hint();
+                let mut a = VecDeque::new();
+                //The height is only a hint, so don't fail if the space can't be reserved.
+                let _ = a.try_reserve(bfs_queue_capacity(levels));
+                a.push_back(self);
+                OrderedIterInner::BreadthFirst(a)
+            }
+        };
+        OrderedIter { inner }
+    }
+
+    ///Provides a dfs preorder iterator that also produces the depth of each element.
+    ///The root is at depth 0.
+    #[inline]
+    fn dfs_preorder_iter_with_depth(self) -> DfsPreOrderIter<LevelIter<Self>> {
+        self.with_depth(Depth(0)).dfs_preorder_iter()
+    }
+
+    ///Provides a dfs inorder iterator that also produces the depth of each element.
+    ///The root is at depth 0.
+    #[inline]
+    fn dfs_inorder_iter_with_depth(self) -> DfsInOrderIter<LevelIter<Self>> {
+        self.with_depth(Depth(0)).dfs_inorder_iter()
+    }
+
+    ///Provides a dfs preorder iterator that also produces whether each element is a leaf.
+    #[inline]
+    fn dfs_preorder_leaf_flagged(self) -> DfsPreOrderIter<LeafFlagged<Self>> {
+        LeafFlagged(self).dfs_preorder_iter()
+    }
+
+    ///Provides a dfs postorder iterator that also produces the depth of each element.
+    ///The root is at depth 0.
+    #[inline]
+    fn dfs_postorder_iter_with_depth(self) -> OrderedIter<LevelIter<Self>> {
+        self.with_depth(Depth(0))
+            .iter_ordered(TraversalOrder::PostOrder)
+    }
+
+    ///Provides an iterator that skips the top num levels of the tree,
+    ///only returning nodes whose depth is at least num. Elements are returned in dfs preorder.
+    #[inline]
+    fn skip_levels(self, num: usize) -> SkipLevels<Self> {
+        SkipLevels {
+            inner: self.dfs_preorder_iter_with_depth(),
+            num,
+        }
+    }
+
+    ///Provides an iterator that only returns the nodes whose depth is a multiple of k,
+    ///along with their depth. The root is at depth 0 so it is always returned.
+    ///Elements are returned in dfs preorder. Panics if k is zero.
+    #[inline]
+    fn every_k_levels(self, k: usize) -> EveryKLevels<Self> {
+        assert!(k > 0, "k must be greater than zero");
+        EveryKLevels {
+            inner: self.dfs_preorder_iter_with_depth(),
+            k,
+        }
+    }
+
+    ///Provides an iterator that returns all the elements the closure produces for each node,
+    ///which can depend on the node's depth. The root is at depth 0.
+    ///Nodes are visited in dfs preorder.
+    #[inline]
+    fn expand_by_level<B, F: Fn(Depth, Self::Item) -> Vec<B>>(
+        self,
+        func: F,
+    ) -> ExpandByLevel<Self, B, F> {
+        ExpandByLevel {
+            inner: self.dfs_preorder_iter_with_depth(),
+            func,
+            current: Vec::new().into_iter(),
+        }
+    }
+
+    ///Provides an iterator that alternates between the dfs preorder elements of this tree and another,
+    ///starting with this one. If one tree is bigger, the remaining elements of it are returned at the end.
+    #[inline]
+    fn interleave<B: Visitor<Item = Self::Item>>(self, other: B) -> Interleave<Self, B> {
+        Interleave {
+            a: self.dfs_preorder_iter(),
+            b: other.dfs_preorder_iter(),
+            a_next: true,
+        }
+    }
+
     ///Calls the closure in dfs preorder (root,left,right).
     ///Takes advantage of the callstack to do dfs.
     #[inline]
@@ -416,18 +1539,430 @@ pub trait Visitor: Sized {
         rec_pre(self, &mut func);
     }
 
+    ///Calls the closure in dfs preorder (root,left,right), not descending past max_depth.
+    ///The root is at depth 0. Uses a heap allocated stack instead of the callstack,
+    ///so it is safe to use on visitors of unknown and possibly huge depth.
+    #[inline]
+    fn dfs_preorder_bounded(self, max_depth: usize, mut func: impl FnMut(Self::Item)) {
+        let mut stack = Vec::new();
+        stack.push((0, self));
+        while let Some((depth, a)) = stack.pop() {
+            let (nn, rest) = a.next();
+            func(nn);
+            if depth < max_depth {
+                if let Some([left, right]) = rest {
+                    stack.push((depth + 1, right));
+                    stack.push((depth + 1, left));
+                }
+            }
+        }
+    }
+
+    ///Calls the closure in dfs preorder (root,left,right), also passing the parent of each element.
+    ///The root gets None.
+    ///Unlike dfs_preorder(), the element is not passed by value. The traversal has to keep it
+    ///alive after the closure returns so that it can be passed as the parent of its children.
+    ///So the closure gets a mutable reference to the element instead, which still allows
+    ///modifying it before its children see it.
+    ///For visitors that produce &mut T, this means the parent can only be read.
+    #[inline]
+    fn dfs_preorder_parent(self, mut func: impl FnMut(Option<&Self::Item>, &mut Self::Item)) {
+        rec_pre_parent(self, None, &mut func);
+    }
+
     ///Calls the closure in dfs preorder (left,right,root).
     ///Takes advantage of the callstack to do dfs.
     #[inline]
-    fn dfs_inorder(self, mut func: impl FnMut(Self::Item)) {
-        rec_inorder(self, &mut func);
+    fn dfs_inorder(self, mut func: impl FnMut(Self::Item)) {
+        rec_inorder(self, &mut func);
+    }
+
+    ///Calls the closure in dfs preorder (left,right,root).
+    ///Takes advantage of the callstack to do dfs.
+    #[inline]
+    fn dfs_postorder(self, mut func: impl FnMut(Self::Item)) {
+        rec_post(self, &mut func);
+    }
+
+    ///Calls the closure in dfs postorder (left,right,root), stopping at the first error.
+    #[inline]
+    fn try_postorder<E>(self, mut func: impl FnMut(Self::Item) -> Result<(), E>) -> Result<(), E> {
+        rec_try_post(self, &mut func)
+    }
+
+    ///Writes the elements in dfs preorder into the buffer, and returns the part of the buffer that was written.
+    ///Panics if the buffer is too small. For a FixedDepthVisitor, the buffer needs to be
+    ///compute_num_nodes(height) long.
+    ///The written elements are not dropped when the buffer is.
+    #[inline]
+    fn collect_preorder_into(self, buffer: &mut [MaybeUninit<Self::Item>]) -> &mut [Self::Item] {
+        let (levels, _) = self.level_remaining_hint();
+        assert!(
+            compute_num_nodes(levels) <= buffer.len(),
+            "buffer is too small"
+        );
+
+        let mut num = 0;
+        self.dfs_preorder(|a| {
+            assert!(num < buffer.len(), "buffer is too small");
+            buffer[num] = MaybeUninit::new(a);
+            num += 1;
+        });
+
+        //Safe since the first num elements were just initialized.
+        unsafe { core::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast::<Self::Item>(), num) }
+    }
+
+    ///Walks down from the root to a leaf, choosing which child to go to at each node, and returns the leaf.
+    ///Useful for binary search tree style lookups.
+    #[inline]
+    fn descend(self, mut choose: impl FnMut(&Self::Item) -> Branch) -> Self::Item {
+        let mut target = self;
+        loop {
+            let (nn, rest) = target.next();
+            match rest {
+                Some([left, right]) => {
+                    target = match choose(&nn) {
+                        Branch::Left => left,
+                        Branch::Right => right,
+                    };
+                }
+                None => return nn,
+            }
+        }
+    }
+
+    ///Returns the dfs preorder position of the first element that satisfies the predicate.
+    ///Stops visiting nodes once one is found.
+    #[inline]
+    fn position_preorder(self, mut pred: impl FnMut(&Self::Item) -> bool) -> Option<usize> {
+        self.dfs_preorder_iter().position(|a| pred(&a))
+    }
+
+    ///Calls the closure on each element in dfs preorder, collecting the Ok values and the Err values
+    ///into two separate vecs. Unlike try_postorder(), this does not stop at the first error.
+    #[inline]
+    fn map_collect_errors<B, E>(
+        self,
+        mut func: impl FnMut(Self::Item) -> Result<B, E>,
+    ) -> (Vec<B>, Vec<E>) {
+        let mut oks = Vec::new();
+        let mut errs = Vec::new();
+        self.dfs_preorder(|a| match func(a) {
+            Ok(b) => oks.push(b),
+            Err(e) => errs.push(e),
+        });
+        (oks, errs)
+    }
+
+    ///Returns how many elements satisfy the predicate.
+    ///Uses dfs_preorder(), so no iterator stack is allocated.
+    #[inline]
+    fn count_preorder(self, mut pred: impl FnMut(&Self::Item) -> bool) -> usize {
+        let mut count = 0;
+        self.dfs_preorder(|a| {
+            if pred(&a) {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    ///Calls the closure on each element in dfs preorder, threading a state through every call,
+    ///and collects the results. The state is passed along in the order nodes are visited,
+    ///not independently per subtree. This matches Iterator::scan() applied to dfs_preorder_iter().
+    #[inline]
+    fn scan_preorder<St, B>(
+        self,
+        init: St,
+        mut func: impl FnMut(&mut St, Self::Item) -> B,
+    ) -> Vec<B> {
+        let mut state = init;
+        let mut res = Vec::new();
+        self.dfs_preorder(|a| res.push(func(&mut state, a)));
+        res
+    }
+
+    ///Reduces the tree bottom up, also passing the depth of each element.
+    ///Leafs are turned into a value with leaf, and non leafs are combined with the values
+    ///of their two children with combine. The root is at start depth.
+    #[inline]
+    fn reduce_depth<B>(
+        self,
+        start: Depth,
+        mut leaf: impl FnMut(Depth, Self::Item) -> B,
+        mut combine: impl FnMut(Depth, Self::Item, B, B) -> B,
+    ) -> B {
+        rec_reduce_depth(self, start, &mut leaf, &mut combine)
+    }
+
+    ///Writes func(item) into out at the bfs index of each node.
+    ///Panics if out is not exactly compute_num_nodes(height) long.
+    #[inline]
+    fn scatter_into_bfs<U>(self, out: &mut [U], mut func: impl FnMut(Self::Item) -> U)
+    where
+        Self: FixedDepthVisitor,
+    {
+        assert_eq!(
+            out.len(),
+            compute_num_nodes(self.get_height()),
+            "out must have one element per node"
+        );
+        rec_scatter_bfs(self, 0, out, &mut func);
+    }
+
+    ///Collects the elements into a container laid out in the dfs order D.
+    ///Each element is placed according to its position in the tree, not the order it is visited in.
+    ///Returns an error if the visitor is not a complete tree, with the number of nodes it had.
+    #[inline]
+    fn collect_container<D: dfs_order::DfsOrder>(
+        self,
+    ) -> Result<dfs_order::CompleteTreeContainer<Self::Item, D>, NotCompleteTreeSizeErr> {
+        let (levels, _) = self.level_remaining_hint();
+        let mut res = Vec::with_capacity(compute_num_nodes(levels));
+        match rec_collect_perfect(self, &mut res) {
+            Some(_) => Ok(dfs_order::CompleteTreeContainer::from_preorder(res)?.into_layout()),
+            None => Err(NotCompleteTreeSizeErr { length: res.len() }),
+        }
+    }
+
+    ///Collects the elements into an owned container so that they can be traversed more than once.
+    ///Useful for visitors that are expensive to produce elements or that cannot be cloned.
+    ///This is collect_container() for visitors that are known to be complete trees.
+    #[inline]
+    fn cache<D: dfs_order::DfsOrder>(
+        self,
+    ) -> Result<dfs_order::CompleteTreeContainer<Self::Item, D>, NotCompleteTreeSizeErr>
+    where
+        Self: FixedDepthVisitor,
+    {
+        self.collect_container()
+    }
+
+    ///Combines the elements of two trees of the same height into a new container laid out in the dfs order D.
+    ///If the heights differ, returns an error with the heights of both trees.
+    #[inline]
+    fn zip_into_container<F: FixedDepthVisitor, U, D: dfs_order::DfsOrder>(
+        self,
+        other: F,
+        func: impl Fn(Self::Item, F::Item) -> U,
+    ) -> Result<dfs_order::CompleteTreeContainer<U, D>, ShapeMismatch>
+    where
+        Self: FixedDepthVisitor,
+    {
+        let func = &func;
+        let res = self
+            .try_zip(other)?
+            .map(move |(a, b)| func(a, b))
+            .collect_container()
+            .expect("two fixed depth visitors of the same height zip into a complete tree");
+        Ok(res)
+    }
+
+    ///Builds a new tree where each node holds a value computed from the element
+    ///and the already computed values of its two children (None for leafs).
+    ///For example, this can produce a tree of subtree sums.
+    ///The tree is built bottom up, so the result is laid out in dfs post order.
+    #[inline]
+    fn subtree_reduce<B>(
+        self,
+        mut combine: impl FnMut(&Self::Item, Option<(&B, &B)>) -> B,
+    ) -> dfs_order::CompleteTreeContainer<B, dfs_order::PostOrder>
+    where
+        Self: FixedDepthVisitor,
+    {
+        let mut res = Vec::with_capacity(compute_num_nodes(self.get_height()));
+        rec_subtree_reduce(self, &mut combine, &mut res);
+        dfs_order::CompleteTreeContainer::from_postorder(res).unwrap()
+    }
+
+    ///Returns the paths from the root to every element that satisfies the predicate, in dfs preorder.
+    #[inline]
+    fn paths_to(self, mut pred: impl FnMut(&Self::Item) -> bool) -> Vec<Vec<Branch>> {
+        let mut res = Vec::new();
+        let mut path = Vec::new();
+        rec_paths_to(self, &mut path, &mut pred, &mut res);
+        res
+    }
+
+    ///Finds the subtrees that appear more than once, comparing both shape and elements.
+    ///Returns one group per distinct repeated subtree, holding the path from the root to each copy.
+    ///Groups are ordered by where their first copy ends in dfs postorder.
+    ///Uses Ord rather than Hash since only BTreeMap is available without std.
+    #[inline]
+    fn find_duplicate_subtrees(self) -> Vec<Vec<Vec<Branch>>>
+    where
+        Self::Item: Ord,
+    {
+        let mut ids = BTreeMap::new();
+        let mut groups = Vec::new();
+        let mut path = Vec::new();
+        rec_duplicate_subtrees(self, &mut path, &mut ids, &mut groups);
+        groups.retain(|a: &Vec<_>| a.len() > 1);
+        groups
+    }
+
+    ///Formats the tree as a Newick string, such as ((A,B)C,(D,E)F)G;
+    ///Leafs are written as just their element, and other nodes as (left,right) followed by their element.
+    #[inline]
+    fn to_newick(self) -> String
+    where
+        Self::Item: core::fmt::Display,
+    {
+        let mut res = String::new();
+        rec_newick(self, &mut res);
+        res.push(';');
+        res
+    }
+
+    ///Folds the elements at each depth together, each level starting from init.
+    ///Returns one result per level, starting with the root's.
+    #[inline]
+    fn fold_levels<B: Clone>(self, init: B, mut func: impl FnMut(B, Self::Item) -> B) -> Vec<B> {
+        let (levels, _) = self.level_remaining_hint();
+        let mut res: Vec<Option<B>> = Vec::with_capacity(levels);
+        self.with_depth(Depth(0)).dfs_preorder(|(depth, a)| {
+            //In preorder a node is always visited after its parent, so levels are reached in order.
+            if depth.0 == res.len() {
+                res.push(Some(init.clone()));
+            }
+            let acc = res[depth.0].take().unwrap();
+            res[depth.0] = Some(func(acc, a));
+        });
+        res.into_iter().map(|a| a.unwrap()).collect()
+    }
+
+    ///Splits the elements into those at even depths and those at odd depths, each in dfs preorder.
+    ///The root is at depth 0, so it is in the first vec.
+    #[inline]
+    fn split_by_level_parity(self) -> (Vec<Self::Item>, Vec<Self::Item>) {
+        let mut even = Vec::new();
+        let mut odd = Vec::new();
+        self.with_depth(Depth(0)).dfs_preorder(|(depth, a)| {
+            if depth.0 % 2 == 0 {
+                even.push(a);
+            } else {
+                odd.push(a);
+            }
+        });
+        (even, odd)
+    }
+
+    ///Groups the elements by their depth. The root is at depth 0.
+    ///Within each depth, the elements are in dfs preorder, so left to right.
+    #[inline]
+    fn group_by_depth(self) -> BTreeMap<usize, Vec<Self::Item>> {
+        let mut map = BTreeMap::new();
+        self.with_depth(Depth(0)).dfs_preorder(|(depth, a)| {
+            map.entry(depth.0).or_insert_with(Vec::new).push(a);
+        });
+        map
+    }
+
+    ///Calls the closure in bfs order.
+    ///If level_remaining_hint() reports a small enough height, the queue is kept on the stack.
+    ///Otherwise a VecDeque is allocated.
+    #[inline]
+    fn bfs(self, mut func: impl FnMut(Self::Item)) {
+        match self.level_remaining_hint().1 {
+            Some(levels) if levels <= BFS_STACK_LEVELS => {
+                let mut queue = ArrayQueue::<Self, { 1 << (BFS_STACK_LEVELS - 1) }>::new();
+                queue.push_back(self);
+                bfs_with(&mut queue, &mut func);
+            }
+            _ => {
+                let (levels, _) = self.level_remaining_hint();
+                let mut queue = VecDeque::new();
+                //The height is only a hint, so don't fail if the space can't be reserved.
+                let _ = queue.try_reserve(bfs_queue_capacity(levels));
+                queue.push_back(self);
+                bfs_with(&mut queue, &mut func);
+            }
+        }
+    }
+
+    ///Returns true iff all leaves are at the same depth, i.e. the visitor describes a perfect
+    ///binary tree. Bails out on the first inconsistency found.
+    ///This is always true for the complete trees in this crate, but is useful for custom visitors.
+    #[allow(clippy::wrong_self_convention)]
+    #[inline]
+    fn is_perfect(self) -> bool {
+        rec_perfect(self).is_some()
+    }
+
+    ///Returns true if at every node, the heights of the left and right subtrees differ by at most one.
+    ///Stops at the first node where they do not.
+    #[inline]
+    #[allow(clippy::wrong_self_convention)]
+    fn is_height_balanced(self) -> bool {
+        rec_height_balanced(self).is_some()
+    }
+
+    ///Returns true if the tree is a mirror image of itself,
+    ///that is if the left subtree is equal to the flipped right subtree.
+    ///Shapes are compared too, so this works for visitors that are not complete trees.
+    #[inline]
+    #[allow(clippy::wrong_self_convention)]
+    fn is_mirror(self) -> bool
+    where
+        Self::Item: PartialEq,
+    {
+        match self.next().1 {
+            Some([left, right]) => rec_mirror(left, right.flip()),
+            None => true,
+        }
+    }
+
+    ///Returns the number of leafs.
+    ///If level_remaining_hint() reports an exact height, this returns without visiting any nodes.
+    #[inline]
+    fn count_leaves(self) -> usize {
+        match self.level_remaining_hint() {
+            (levels, Some(max)) if levels == max && levels > 0 => 1 << (levels - 1),
+            _ => self.reduce_depth(Depth(0), |_, _| 1, |_, _, left, right| left + right),
+        }
+    }
+
+    ///Returns the number of nodes that have children.
+    ///If level_remaining_hint() reports an exact height, this returns without visiting any nodes.
+    #[inline]
+    fn count_internal(self) -> usize {
+        match self.level_remaining_hint() {
+            (levels, Some(max)) if levels == max && levels > 0 => (1 << (levels - 1)) - 1,
+            _ => self.reduce_depth(Depth(0), |_, _| 0, |_, _, left, right| left + right + 1),
+        }
+    }
+
+    ///Returns the number of nodes whose depth is greater than the given depth. The root is at depth 0.
+    ///If level_remaining_hint() reports an exact height, this returns without visiting any nodes.
+    #[inline]
+    fn count_below_depth(self, depth: usize) -> usize {
+        match self.level_remaining_hint() {
+            (levels, Some(max)) if levels == max && levels > 0 => {
+                let above = depth.saturating_add(1).min(levels);
+                compute_num_nodes(levels) - compute_num_nodes(above)
+            }
+            _ => {
+                let mut count = 0;
+                self.with_depth(Depth(0)).dfs_preorder(|(d, _)| {
+                    if d.0 > depth {
+                        count += 1;
+                    }
+                });
+                count
+            }
+        }
     }
 
-    ///Calls the closure in dfs preorder (left,right,root).
-    ///Takes advantage of the callstack to do dfs.
+    ///Returns the depth of the shallowest and deepest leaf, with the root at depth zero.
+    ///For the built in complete trees these are always equal.
     #[inline]
-    fn dfs_postorder(self, mut func: impl FnMut(Self::Item)) {
-        rec_post(self, &mut func);
+    fn leaf_depth_stats(self) -> (usize, usize) {
+        self.reduce_depth(
+            Depth(0),
+            |depth, _| (depth.0, depth.0),
+            |_, _, (lmin, lmax), (rmin, rmax)| (lmin.min(rmin), lmax.max(rmax)),
+        )
     }
 }
 
@@ -443,6 +1978,19 @@ fn rec_pre<C: Visitor>(a: C, func: &mut impl FnMut(C::Item)) {
         None => func(nn),
     }
 }
+fn rec_pre_parent<C: Visitor>(
+    a: C,
+    parent: Option<&C::Item>,
+    func: &mut impl FnMut(Option<&C::Item>, &mut C::Item),
+) {
+    let (mut nn, rest) = a.next();
+    func(parent, &mut nn);
+
+    if let Some([left, right]) = rest {
+        rec_pre_parent(left, Some(&nn), func);
+        rec_pre_parent(right, Some(&nn), func);
+    }
+}
 fn rec_inorder<C: Visitor>(a: C, func: &mut impl FnMut(C::Item)) {
     let (nn, rest) = a.next();
 
@@ -471,6 +2019,371 @@ fn rec_post<C: Visitor>(a: C, func: &mut impl FnMut(C::Item)) {
         }
     }
 }
+fn rec_scatter_bfs<C: Visitor, U>(
+    a: C,
+    index: usize,
+    out: &mut [U],
+    func: &mut impl FnMut(C::Item) -> U,
+) {
+    let (nn, rest) = a.next();
+    out[index] = func(nn);
+
+    if let Some([left, right]) = rest {
+        rec_scatter_bfs(left, 2 * index + 1, out, func);
+        rec_scatter_bfs(right, 2 * index + 2, out, func);
+    }
+}
+
+fn rec_paths_to<C: Visitor>(
+    a: C,
+    path: &mut Vec<Branch>,
+    pred: &mut impl FnMut(&C::Item) -> bool,
+    res: &mut Vec<Vec<Branch>>,
+) {
+    let (nn, rest) = a.next();
+    if pred(&nn) {
+        res.push(path.clone());
+    }
+
+    if let Some([left, right]) = rest {
+        path.push(Branch::Left);
+        rec_paths_to(left, path, pred, res);
+        path.pop();
+        path.push(Branch::Right);
+        rec_paths_to(right, path, pred, res);
+        path.pop();
+    }
+}
+
+//Maps an element and the ids of its children to the id of that subtree.
+type SubtreeIds<T> = BTreeMap<(T, Option<(usize, usize)>), usize>;
+
+//Returns an id that is the same for any two subtrees with the same shape and elements.
+fn rec_duplicate_subtrees<C: Visitor>(
+    a: C,
+    path: &mut Vec<Branch>,
+    ids: &mut SubtreeIds<C::Item>,
+    groups: &mut Vec<Vec<Vec<Branch>>>,
+) -> usize
+where
+    C::Item: Ord,
+{
+    let (nn, rest) = a.next();
+
+    let children = rest.map(|[left, right]| {
+        path.push(Branch::Left);
+        let left = rec_duplicate_subtrees(left, path, ids, groups);
+        path.pop();
+        path.push(Branch::Right);
+        let right = rec_duplicate_subtrees(right, path, ids, groups);
+        path.pop();
+        (left, right)
+    });
+
+    let next_id = ids.len();
+    let id = *ids.entry((nn, children)).or_insert(next_id);
+    if id == groups.len() {
+        groups.push(Vec::new());
+    }
+    groups[id].push(path.clone());
+    id
+}
+
+fn rec_mirror<A: Visitor, B: Visitor<Item = A::Item>>(a: A, b: B) -> bool
+where
+    A::Item: PartialEq,
+{
+    let (a, arest) = a.next();
+    let (b, brest) = b.next();
+    if a != b {
+        return false;
+    }
+    match (arest, brest) {
+        (Some([al, ar]), Some([bl, br])) => rec_mirror(al, bl) && rec_mirror(ar, br),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn rec_newick<C: Visitor>(a: C, res: &mut String)
+where
+    C::Item: core::fmt::Display,
+{
+    use core::fmt::Write;
+    let (nn, rest) = a.next();
+
+    if let Some([left, right]) = rest {
+        res.push('(');
+        rec_newick(left, res);
+        res.push(',');
+        rec_newick(right, res);
+        res.push(')');
+    }
+    //Writing to a String never fails.
+    write!(res, "{}", nn).unwrap();
+}
+
+fn rec_try_post<C: Visitor, E>(
+    a: C,
+    func: &mut impl FnMut(C::Item) -> Result<(), E>,
+) -> Result<(), E> {
+    let (nn, rest) = a.next();
+
+    if let Some([left, right]) = rest {
+        rec_try_post(left, func)?;
+        rec_try_post(right, func)?;
+    }
+    func(nn)
+}
+//The max height for which Visitor::bfs() will keep its queue on the stack.
+const BFS_STACK_LEVELS: usize = 5;
+
+//Need enough room to fit all the leafs in the queue at once, of which there are n/2.
+//Saturates instead of overflowing for heights that could never fit in memory.
+fn bfs_queue_capacity(levels: usize) -> usize {
+    1usize
+        .checked_shl(levels.min(u32::MAX as usize) as u32)
+        .map_or(usize::MAX, |num| num / 2)
+}
+
+trait BfsQueue<C> {
+    fn push_back(&mut self, a: C);
+    fn pop_front(&mut self) -> Option<C>;
+}
+
+impl<C> BfsQueue<C> for VecDeque<C> {
+    #[inline]
+    fn push_back(&mut self, a: C) {
+        VecDeque::push_back(self, a)
+    }
+    #[inline]
+    fn pop_front(&mut self) -> Option<C> {
+        VecDeque::pop_front(self)
+    }
+}
+
+//Fixed capacity ring buffer.
+struct ArrayQueue<C, const N: usize> {
+    buffer: [Option<C>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<C, const N: usize> ArrayQueue<C, N> {
+    fn new() -> Self {
+        ArrayQueue {
+            buffer: [(); N].map(|_| None),
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<C, const N: usize> BfsQueue<C> for ArrayQueue<C, N> {
+    #[inline]
+    fn push_back(&mut self, a: C) {
+        assert!(
+            self.len < N,
+            "level_remaining_hint() returned an upper bound that was too small"
+        );
+        self.buffer[(self.head + self.len) % N] = Some(a);
+        self.len += 1;
+    }
+    #[inline]
+    fn pop_front(&mut self) -> Option<C> {
+        if self.len == 0 {
+            None
+        } else {
+            let a = self.buffer[self.head].take();
+            self.head = (self.head + 1) % N;
+            self.len -= 1;
+            a
+        }
+    }
+}
+
+fn bfs_with<C: Visitor>(queue: &mut impl BfsQueue<C>, func: &mut impl FnMut(C::Item)) {
+    while let Some(a) = queue.pop_front() {
+        let (nn, rest) = a.next();
+        if let Some([left, right]) = rest {
+            queue.push_back(left);
+            queue.push_back(right);
+        }
+        func(nn);
+    }
+}
+
+fn rec_reduce_depth<C: Visitor, B>(
+    a: C,
+    depth: Depth,
+    leaf: &mut impl FnMut(Depth, C::Item) -> B,
+    combine: &mut impl FnMut(Depth, C::Item, B, B) -> B,
+) -> B {
+    let (nn, rest) = a.next();
+
+    match rest {
+        Some([left, right]) => {
+            let next = depth.incr();
+            let left = rec_reduce_depth(left, next, leaf, combine);
+            let right = rec_reduce_depth(right, next, leaf, combine);
+            combine(depth, nn, left, right)
+        }
+        None => leaf(depth, nn),
+    }
+}
+
+fn rec_subtree_reduce<C: Visitor, B>(
+    a: C,
+    func: &mut impl FnMut(&C::Item, Option<(&B, &B)>) -> B,
+    res: &mut Vec<B>,
+) {
+    let (nn, rest) = a.next();
+
+    let b = match rest {
+        Some([left, right]) => {
+            rec_subtree_reduce(left, func, res);
+            let left = res.len() - 1;
+            rec_subtree_reduce(right, func, res);
+            let right = res.len() - 1;
+            func(&nn, Some((&res[left], &res[right])))
+        }
+        None => func(&nn, None),
+    };
+    res.push(b);
+}
+
+//Returns the height of the subtree if it is perfect.
+//Pushes the elements in dfs preorder, and returns the height if the tree is perfect.
+//Unlike rec_perfect(), this keeps going after finding the tree is not perfect.
+fn rec_collect_perfect<C: Visitor>(a: C, res: &mut Vec<C::Item>) -> Option<usize> {
+    let (nn, rest) = a.next();
+    res.push(nn);
+
+    match rest {
+        Some([left, right]) => {
+            let left = rec_collect_perfect(left, res);
+            let right = rec_collect_perfect(right, res);
+            match (left, right) {
+                (Some(left), Some(right)) if left == right => Some(left + 1),
+                _ => None,
+            }
+        }
+        None => Some(1),
+    }
+}
+
+fn rec_height_balanced<C: Visitor>(a: C) -> Option<usize> {
+    let (_, rest) = a.next();
+
+    match rest {
+        Some([left, right]) => {
+            let left = rec_height_balanced(left)?;
+            let right = rec_height_balanced(right)?;
+            if left.abs_diff(right) <= 1 {
+                Some(left.max(right) + 1)
+            } else {
+                None
+            }
+        }
+        None => Some(1),
+    }
+}
+
+fn rec_perfect<C: Visitor>(a: C) -> Option<usize> {
+    let (_, rest) = a.next();
+
+    match rest {
+        Some([left, right]) => {
+            let left = rec_perfect(left)?;
+            let right = rec_perfect(right)?;
+            if left == right {
+                Some(left + 1)
+            } else {
+                None
+            }
+        }
+        None => Some(1),
+    }
+}
+
+///Create a visitor that treats a single item as a tree of height 1.
+#[inline]
+pub fn leaf<T>(item: T) -> Leaf<T> {
+    Leaf(item)
+}
+
+///Tree visitor of a single node with no children. Produced by leaf().
+#[derive(Copy, Clone, Debug)]
+pub struct Leaf<T>(T);
+impl<T> Visitor for Leaf<T> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        (self.0, None)
+    }
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        (1, Some(1))
+    }
+}
+impl<T> FixedDepthVisitor for Leaf<T> {}
+
+///Create a visitor from a seed and a closure that turns a seed into an element
+///and, for non leafs, the seeds of its two children.
+///The tree analog of core::iter::from_fn().
+#[inline]
+pub fn unfold<S, T, F: Fn(S) -> (T, Option<(S, S)>) + Clone>(seed: S, func: F) -> Unfold<S, F> {
+    Unfold { seed, func }
+}
+
+///Tree visitor defined by a closure. Produced by unfold().
+#[derive(Clone)]
+pub struct Unfold<S, F> {
+    seed: S,
+    func: F,
+}
+impl<S, T, F: Fn(S) -> (T, Option<(S, S)>) + Clone> Visitor for Unfold<S, F> {
+    type Item = T;
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let Unfold { seed, func } = self;
+        let (a, rest) = func(seed);
+        let rest = rest.map(|(left, right)| {
+            [
+                Unfold {
+                    seed: left,
+                    func: func.clone(),
+                },
+                Unfold { seed: right, func },
+            ]
+        });
+        (a, rest)
+    }
+}
+
+///Tree visitor that also produces whether each element is a leaf.
+///Produced by Visitor::dfs_preorder_leaf_flagged().
+#[derive(Clone)]
+pub struct LeafFlagged<T: Visitor>(T);
+impl<T: Visitor> Visitor for LeafFlagged<T> {
+    type Item = (T::Item, bool);
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.0.next();
+        let is_leaf = rest.is_none();
+        (
+            (a, is_leaf),
+            rest.map(|[l, r]| [LeafFlagged(l), LeafFlagged(r)]),
+        )
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.0.level_remaining_hint()
+    }
+}
+impl<T: FixedDepthVisitor> FixedDepthVisitor for LeafFlagged<T> {}
 
 ///Flips left and right children.
 #[derive(Clone)]
@@ -481,9 +2394,56 @@ impl<T: Visitor> Visitor for Flip<T> {
         let (a, rest) = self.0.next();
         (a, rest.map(|[l, r]| [Flip(r), Flip(l)]))
     }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.0.level_remaining_hint()
+    }
 }
 impl<T: FixedDepthVisitor> FixedDepthVisitor for Flip<T> {}
 
+///Flips left and right children only at some depths.
+#[derive(Clone)]
+pub struct FlipAt<T, F> {
+    inner: T,
+    depth: usize,
+    should_flip: F,
+}
+impl<T: Visitor, F: Fn(Depth) -> bool + Clone> Visitor for FlipAt<T, F> {
+    type Item = T::Item;
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, rest) = self.inner.next();
+        let should_flip = self.should_flip;
+        let flip = should_flip(Depth(self.depth));
+        let depth = self.depth + 1;
+
+        let rest = rest.map(|[left, right]| {
+            let (left, right) = if flip { (right, left) } else { (left, right) };
+            [
+                FlipAt {
+                    inner: left,
+                    depth,
+                    should_flip: should_flip.clone(),
+                },
+                FlipAt {
+                    inner: right,
+                    depth,
+                    should_flip,
+                },
+            ]
+        });
+        (a, rest)
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
+    }
+}
+impl<T: FixedDepthVisitor, F: Fn(Depth) -> bool + Clone> FixedDepthVisitor for FlipAt<T, F> {}
+
 ///Only returns children up until level num.
 #[derive(Clone)]
 pub struct Take<T: Visitor> {
@@ -518,6 +2478,85 @@ impl<T: Visitor> Visitor for Take<T> {
         };
         (a, rest)
     }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        let levels = self.num + 1;
+        min_hint(self.a.level_remaining_hint(), (levels, Some(levels)))
+    }
+}
+
+//Combines the level_remaining_hint() of two visitors, for a visitor that stops producing
+//children as soon as either of them does.
+//The lower bound is only what both guarantee. A known upper bound on either side caps the result,
+//even if the other side's upper bound is unknown.
+fn min_hint(a: (usize, Option<usize>), b: (usize, Option<usize>)) -> (usize, Option<usize>) {
+    let min = a.0.min(b.0);
+
+    let min2 = match (a.1, b.1) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (Some(a), None) | (None, Some(a)) => Some(a),
+        (None, None) => None,
+    };
+
+    (min, min2)
+}
+
+///Tree visitor that zips up two separate visitors, continuing to the deeper of the two.
+///Produced by Visitor::zip_pad(). At least one side is always Some.
+#[derive(Clone)]
+pub struct ZipPad<T1: Visitor, T2: Visitor> {
+    a: Option<T1>,
+    b: Option<T2>,
+}
+
+impl<T1: Visitor, T2: Visitor> Visitor for ZipPad<T1, T2> {
+    type Item = (Option<T1::Item>, Option<T2::Item>);
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let (a, (al, ar)) = pad_next(self.a);
+        let (b, (bl, br)) = pad_next(self.b);
+
+        let rest = if al.is_none() && bl.is_none() {
+            None
+        } else {
+            Some([ZipPad { a: al, b: bl }, ZipPad { a: ar, b: br }])
+        };
+        ((a, b), rest)
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        let hint = |a: Option<(usize, Option<usize>)>| a.unwrap_or((0, Some(0)));
+        let a = hint(self.a.as_ref().map(|a| a.level_remaining_hint()));
+        let b = hint(self.b.as_ref().map(|b| b.level_remaining_hint()));
+        let max = match (a.1, b.1) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            _ => None,
+        };
+        (a.0.max(b.0), max)
+    }
+}
+impl<T1: FixedDepthVisitor, T2: FixedDepthVisitor> FixedDepthVisitor for ZipPad<T1, T2> {}
+
+//Like Visitor::next(), but for a side of ZipPad that may have run out.
+#[allow(clippy::type_complexity)]
+fn pad_next<C: Visitor>(a: Option<C>) -> (Option<C::Item>, (Option<C>, Option<C>)) {
+    match a.map(|a| a.next()) {
+        Some((a, Some([left, right]))) => (Some(a), (Some(left), Some(right))),
+        Some((a, None)) => (Some(a), (None, None)),
+        None => (None, (None, None)),
+    }
+}
+
+///Tree visitor that produces the ordering between the elements of two visitors.
+///Produced by Visitor::zip_cmp().
+pub type ZipCmp<T1, T2> =
+    Map<Zip<T1, T2>, fn((<T1 as Visitor>::Item, <T1 as Visitor>::Item)) -> Ordering>;
+
+fn cmp_pair<T: Ord>((a, b): (T, T)) -> Ordering {
+    a.cmp(&b)
 }
 
 ///Tree visitor that zips up two separate visitors.
@@ -570,24 +2609,127 @@ impl<T1: Visitor, T2: Visitor> Visitor for Zip<T1, T2> {
 
     #[inline]
     fn level_remaining_hint(&self) -> (usize, Option<usize>) {
-        let a = self.a.level_remaining_hint();
-        let b = self.b.level_remaining_hint();
-        let min = a.0.min(b.0);
+        min_hint(self.a.level_remaining_hint(), self.b.level_remaining_hint())
+    }
+}
+impl<T1: FixedDepthVisitor, T2: FixedDepthVisitor> FixedDepthVisitor for Zip<T1, T2> {}
 
-        let min2 = match (a.1, b.1) {
-            (Some(a), Some(b)) => Some(a.min(b)),
-            _ => None,
-        };
+///Tree visitor that zips up two separate visitors and also produces the depth of each element.
+///Like Zip, if one of the visitors returns None for its children, this visitor will return None.
+#[derive(Clone)]
+pub struct ZipDepth<T1: Visitor, T2: Visitor> {
+    inner: LevelIter<Zip<T1, T2>>,
+}
+
+impl<T1: Visitor, T2: Visitor> Visitor for ZipDepth<T1, T2> {
+    type Item = (Depth, T1::Item, T2::Item);
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let ((depth, (a, b)), rest) = self.inner.next();
+        (
+            (depth, a, b),
+            rest.map(|[left, right]| [ZipDepth { inner: left }, ZipDepth { inner: right }]),
+        )
+    }
 
-        (min, min2)
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        self.inner.level_remaining_hint()
     }
 }
-impl<T1: FixedDepthVisitor, T2: FixedDepthVisitor> FixedDepthVisitor for Zip<T1, T2> {}
+impl<T1: FixedDepthVisitor, T2: FixedDepthVisitor> FixedDepthVisitor for ZipDepth<T1, T2> {}
+
+///Tree visitor that zips up two separate visitors and maps each pair along with its depth.
+///Produced by Visitor::zip_depth_map(). Does zip(), with_depth() and map() in one adapter.
+#[derive(Clone)]
+pub struct ZipDepthMap<T1: Visitor, T2: Visitor, F> {
+    a: T1,
+    b: T2,
+    depth: usize,
+    func: F,
+}
+
+impl<O, T1: Visitor, T2: Visitor, F: Fn(Depth, T1::Item, T2::Item) -> O + Clone> Visitor
+    for ZipDepthMap<T1, T2, F>
+{
+    type Item = O;
+
+    #[inline]
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        let ZipDepthMap { a, b, depth, func } = self;
+        let (a, arest) = a.next();
+        let (b, brest) = b.next();
+        let item = func(Depth(depth), a, b);
+
+        match (arest, brest) {
+            (Some([al, ar]), Some([bl, br])) => {
+                let depth = depth + 1;
+                let left = ZipDepthMap {
+                    a: al,
+                    b: bl,
+                    depth,
+                    func: func.clone(),
+                };
+                let right = ZipDepthMap {
+                    a: ar,
+                    b: br,
+                    depth,
+                    func,
+                };
+                (item, Some([left, right]))
+            }
+            _ => (item, None),
+        }
+    }
+
+    #[inline]
+    fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+        min_hint(self.a.level_remaining_hint(), self.b.level_remaining_hint())
+    }
+}
+impl<
+        O,
+        T1: FixedDepthVisitor,
+        T2: FixedDepthVisitor,
+        F: Fn(Depth, T1::Item, T2::Item) -> O + Clone,
+    > FixedDepthVisitor for ZipDepthMap<T1, T2, F>
+{
+}
+
+///Specifies one of the two children of a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Branch {
+    Left,
+    Right,
+}
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 ///A level descriptor.
 pub struct Depth(pub usize);
 
+impl Depth {
+    ///Returns the depth one level below this one.
+    #[inline]
+    pub fn incr(self) -> Depth {
+        Depth(self.0 + 1)
+    }
+}
+
+impl From<usize> for Depth {
+    #[inline]
+    fn from(a: usize) -> Depth {
+        Depth(a)
+    }
+}
+
+impl From<Depth> for usize {
+    #[inline]
+    fn from(a: Depth) -> usize {
+        a.0
+    }
+}
+
 ///A wrapper iterator that will additionally return the depth of each element.
 #[derive(Clone)]
 pub struct LevelIter<T> {
@@ -623,7 +2765,7 @@ impl<T: Visitor> Visitor for LevelIter<T> {
         let r = (depth, nn);
         match rest {
             Some([left, right]) => {
-                let ln = Depth(depth.0 + 1);
+                let ln = depth.incr();
                 let ll = LevelIter {
                     inner: left,
                     depth: ln,
@@ -1,6 +1,21 @@
 extern crate compt;
 use compt::*;
 
+///A custom visitor whose left children are always leaves,
+///and whose right children continue down for the given number of levels.
+#[derive(Clone)]
+struct Lopsided(usize);
+impl Visitor for Lopsided {
+    type Item = usize;
+    fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+        if self.0 == 0 {
+            (0, None)
+        } else {
+            (self.0, Some([Lopsided(0), Lopsided(self.0 - 1)]))
+        }
+    }
+}
+
 #[test]
 fn dfs_mut() {
     let mut k =
@@ -85,3 +100,1900 @@ fn test_derefs(){
     assert_eq!(ans,&[0usize,1,3,4,2,5,6]);
 }
 */
+
+#[test]
+fn dfs_preorder_iter_with_depth() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .dfs_preorder_iter_with_depth()
+        .map(|(d, a)| (d.0, *a))
+        .collect();
+    assert_eq!(
+        &res,
+        &[(0, 3), (1, 1), (2, 0), (2, 2), (1, 5), (2, 4), (2, 6)]
+    );
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .dfs_inorder_iter_with_depth()
+        .map(|(d, a)| (d.0, *a))
+        .collect();
+    assert_eq!(
+        &res,
+        &[(2, 0), (1, 1), (2, 2), (0, 3), (2, 4), (1, 5), (2, 6)]
+    );
+}
+
+#[test]
+fn is_perfect() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    assert!(k.as_tree().vistr().is_perfect());
+
+    assert!(Lopsided(0).is_perfect());
+    assert!(Lopsided(1).is_perfect());
+    assert!(!Lopsided(2).is_perfect());
+}
+
+#[test]
+fn checked_num_nodes() {
+    assert_eq!(compt::checked_num_nodes(0), Some(0));
+    assert_eq!(compt::checked_num_nodes(3), Some(7));
+    assert_eq!(compt::compute_num_nodes(3), 7);
+    assert_eq!(
+        compt::checked_num_nodes(usize::BITS as usize),
+        Some(usize::MAX)
+    );
+    assert_eq!(compt::checked_num_nodes(usize::BITS as usize + 1), None);
+    assert_eq!(compt::checked_num_nodes(200), None);
+}
+
+#[test]
+fn skip_levels() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res: Vec<_> = k.as_tree().vistr().skip_levels(1).cloned().collect();
+    assert_eq!(&res, &[1, 0, 2, 5, 4, 6]);
+
+    let res: Vec<_> = k.as_tree().vistr().skip_levels(2).cloned().collect();
+    assert_eq!(&res, &[0, 2, 4, 6]);
+
+    assert_eq!(k.as_tree().vistr().skip_levels(3).count(), 0);
+}
+
+#[test]
+fn vistr_len() {
+    let mut k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..15).collect::<Vec<usize>>())
+            .unwrap();
+
+    let v = k.as_tree().vistr();
+    assert_eq!(v.len(), 15);
+    assert!(!v.is_empty());
+    let (_, rest) = v.next();
+    let [left, right] = rest.unwrap();
+    assert_eq!(left.len(), 7);
+    assert_eq!(right.len(), 7);
+    let (_, rest) = left.next();
+    let [left, _] = rest.unwrap();
+    assert_eq!(left.len(), 3);
+
+    let v = k.as_tree_mut().vistr_mut();
+    assert_eq!(v.len(), 15);
+    let (_, rest) = v.next();
+    let [_, right] = rest.unwrap();
+    assert_eq!(right.len(), 7);
+    assert!(!right.is_empty());
+}
+
+#[test]
+fn zip_depth() {
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![10, 11, 12, 13, 14, 15, 16])
+            .unwrap();
+
+    let res: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .zip_depth(k2.as_tree().vistr())
+        .dfs_preorder_iter()
+        .map(|(d, a, b)| (d.0, *a, *b))
+        .collect();
+    assert_eq!(
+        &res,
+        &[
+            (0, 3, 10),
+            (1, 1, 11),
+            (2, 0, 12),
+            (2, 2, 13),
+            (1, 5, 14),
+            (2, 4, 15),
+            (2, 6, 16)
+        ]
+    );
+
+    //Children stop at the shorter of the two trees.
+    let k3 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![20, 21, 22]).unwrap();
+    let res: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .zip_depth(k3.as_tree().vistr())
+        .dfs_preorder_iter()
+        .map(|(d, a, b)| (d.0, *a, *b))
+        .collect();
+    assert_eq!(&res, &[(0, 3, 20), (1, 1, 21), (1, 5, 22)]);
+}
+
+#[test]
+fn bfs() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![3, 1, 4, 0, 5, 2, 6]).unwrap();
+
+    let mut res = Vec::new();
+    k.as_tree().vistr().bfs(|a| res.push(*a));
+    assert_eq!(&res, &[0, 1, 2, 3, 4, 5, 6]);
+
+    //Largest tree that keeps the queue on the stack.
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 31]).unwrap();
+    let mut num = 0;
+    k.as_tree().vistr().bfs(|_| num += 1);
+    assert_eq!(num, 31);
+
+    //Too big to keep the queue on the stack.
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 127]).unwrap();
+    let mut num = 0;
+    k.as_tree().vistr().bfs(|_| num += 1);
+    assert_eq!(num, 127);
+
+    //No hint, so the queue is on the heap.
+    let mut res = Vec::new();
+    Lopsided(2).bfs(|a| res.push(a));
+    assert_eq!(&res, &[2, 0, 1, 0, 0]);
+
+    //A lower bound too large to preallocate for does not panic.
+    struct Deep(usize);
+    impl Visitor for Deep {
+        type Item = usize;
+        fn next(self) -> (usize, Option<[Self; 2]>) {
+            (self.0, None)
+        }
+        fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+            (self.0, None)
+        }
+    }
+    for levels in [62, 63, 64, 1000] {
+        let mut res = Vec::new();
+        Deep(levels).bfs(|a| res.push(a));
+        assert_eq!(res, [levels]);
+    }
+}
+
+#[test]
+fn scan_preorder() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res = k.as_tree().vistr().scan_preorder(0, |acc, a| {
+        *acc += *a;
+        *acc
+    });
+    assert_eq!(&res, &[3, 4, 4, 6, 11, 15, 21]);
+}
+
+#[test]
+fn interleave() {
+    let k1 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2]).unwrap();
+    let k2 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![10, 11, 12]).unwrap();
+
+    let res: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .interleave(k2.as_tree().vistr())
+        .cloned()
+        .collect();
+    assert_eq!(&res, &[0, 10, 1, 11, 2, 12]);
+
+    let k3 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![20, 21, 22, 23, 24, 25, 26])
+            .unwrap();
+    let it = k1.as_tree().vistr().interleave(k3.as_tree().vistr());
+    assert_eq!(it.size_hint(), (10, Some(10)));
+    let res: Vec<_> = it.cloned().collect();
+    assert_eq!(&res, &[0, 20, 1, 21, 2, 22, 23, 24, 25, 26]);
+}
+
+#[test]
+fn dfs_inorder_rev_iter() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![3, 1, 2, 0, 4, 5, 6]).unwrap();
+
+    let mut forward: Vec<_> = k.as_tree().vistr().dfs_inorder_iter().collect();
+    forward.reverse();
+
+    let it = k.as_tree().vistr().dfs_inorder_rev_iter();
+    assert_eq!(it.len(), 7);
+    let backward: Vec<_> = it.collect();
+    assert_eq!(forward, backward);
+    assert_eq!(
+        backward.into_iter().cloned().collect::<Vec<_>>(),
+        &[6, 4, 5, 3, 0, 1, 2]
+    );
+}
+
+#[test]
+fn from_nodes_mut_unchecked() {
+    let mut k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let nodes = k.as_tree_mut().get_nodes_mut();
+    nodes[3] = 10;
+
+    let tree: compt::dfs_order::CompleteTreeMut<_, compt::dfs_order::InOrder> =
+        unsafe { compt::dfs_order::CompleteTreeMut::from_nodes_mut_unchecked(nodes) };
+    let (root, _) = tree.vistr_mut().next();
+    assert_eq!(*root, 10);
+}
+
+#[test]
+fn dfs_preorder_parent() {
+    fn is_max_heap(
+        k: &compt::dfs_order::CompleteTreeContainer<i32, compt::dfs_order::PreOrder>,
+    ) -> bool {
+        let mut valid = true;
+        k.as_tree().vistr().dfs_preorder_parent(|parent, a| {
+            if let Some(parent) = parent {
+                valid &= **a <= **parent;
+            }
+        });
+        valid
+    }
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![9, 5, 3, 4, 8, 6, 7]).unwrap();
+    assert!(is_max_heap(&k));
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![9, 5, 3, 6, 8, 6, 7]).unwrap();
+    assert!(!is_max_heap(&k));
+
+    //The root has no parent, and each node can be written based on its parent.
+    let mut k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0; 7]).unwrap();
+    k.as_tree_mut()
+        .vistr_mut()
+        .dfs_preorder_parent(|parent, a| match parent {
+            Some(parent) => **a = **parent + 1,
+            None => **a = 10,
+        });
+    assert_eq!(&*k.into_nodes(), &[12, 11, 12, 10, 12, 11, 12]);
+}
+
+#[test]
+fn subtree_reduce() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let sums = k
+        .as_tree()
+        .vistr()
+        .subtree_reduce(|a, children| match children {
+            Some((left, right)) => **a + *left + *right,
+            None => **a,
+        });
+
+    let (root, _) = sums.as_tree().vistr().next();
+    assert_eq!(*root, 21);
+    assert_eq!(&*sums.into_nodes(), &[0, 2, 3, 4, 6, 15, 21]);
+}
+
+#[test]
+fn position_preorder() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let pos = k.as_tree().vistr().position_preorder(|a| **a == 5);
+    assert_eq!(pos, Some(4));
+
+    let preorder: Vec<_> = k.as_tree().vistr().dfs_preorder_iter().collect();
+    assert_eq!(*preorder[pos.unwrap()], 5);
+
+    assert_eq!(k.as_tree().vistr().position_preorder(|a| **a == 7), None);
+}
+
+#[test]
+fn exact_size_len() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+
+    let mut it = k.as_tree().vistr().dfs_preorder_iter();
+    for i in (0..15).rev() {
+        it.next().unwrap();
+        assert_eq!(it.len(), i);
+    }
+    assert!(it.next().is_none());
+
+    let mut it = k.as_tree().vistr().dfs_inorder_iter();
+    for i in (0..15).rev() {
+        it.next().unwrap();
+        assert_eq!(it.len(), i);
+        assert_eq!(it.size_hint(), (i, Some(i)));
+    }
+    assert!(it.next().is_none());
+
+    let mut it = k.as_tree().vistr().dfs_inorder_rev_iter();
+    for i in (0..15).rev() {
+        it.next().unwrap();
+        assert_eq!(it.len(), i);
+    }
+    assert!(it.next().is_none());
+}
+
+#[test]
+fn leaf() {
+    let mut res = Vec::new();
+    compt::leaf(5).dfs_preorder(|a| res.push(a));
+    assert_eq!(&res, &[5]);
+
+    assert_eq!(compt::leaf(5).level_remaining_hint(), (1, Some(1)));
+    assert_eq!(compt::leaf(5).dfs_preorder_iter().len(), 1);
+}
+
+#[test]
+fn dfs_preorder_boxed() {
+    let k1 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2]).unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![4, 3, 5, 6, 8, 7, 9]).unwrap();
+
+    let iters: Vec<Box<dyn Iterator<Item = i32>>> = vec![
+        k1.as_tree().vistr().map(|a| *a).dfs_preorder_boxed(),
+        k2.as_tree().vistr().map(|a| *a).dfs_preorder_boxed(),
+        compt::leaf(10).dfs_preorder_boxed(),
+    ];
+
+    let res: Vec<_> = iters.into_iter().flatten().collect();
+    assert_eq!(&res, &[0, 1, 2, 6, 3, 4, 5, 7, 8, 9, 10]);
+}
+
+#[test]
+fn swap_subtrees() {
+    let mut k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    k.as_tree_mut().swap_subtrees(&[]);
+    assert_eq!(&*k.clone().into_nodes(), &[4, 5, 6, 3, 0, 1, 2]);
+
+    k.as_tree_mut().swap_subtrees(&[Branch::Right]);
+    assert_eq!(&*k.clone().into_nodes(), &[4, 5, 6, 3, 2, 1, 0]);
+
+    //Swapping at a leaf does nothing.
+    k.as_tree_mut().swap_subtrees(&[Branch::Left, Branch::Left]);
+    assert_eq!(&*k.into_nodes(), &[4, 5, 6, 3, 2, 1, 0]);
+
+    let mut k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    k.as_tree_mut().swap_subtrees(&[]);
+    assert_eq!(&*k.into_nodes(), &[0, 4, 5, 6, 1, 2, 3]);
+}
+
+#[test]
+fn root() {
+    use compt::dfs_order::*;
+    let nodes: Vec<_> = (0..7).collect();
+    let mut k = CompleteTreeContainer::from_preorder(nodes.clone()).unwrap();
+    assert_eq!(*k.as_tree().root(), 0);
+    *k.as_tree_mut().root_mut() = 10;
+    assert_eq!(k.as_tree().vistr().next().0, &10);
+
+    let mut k = CompleteTreeContainer::from_inorder(nodes.clone()).unwrap();
+    assert_eq!(*k.as_tree().root(), 3);
+    *k.as_tree_mut().root_mut() = 10;
+    assert_eq!(k.as_tree().vistr().next().0, &10);
+
+    let mut k = CompleteTreeContainer::from_postorder(nodes).unwrap();
+    assert_eq!(*k.as_tree().root(), 6);
+    *k.as_tree_mut().root_mut() = 10;
+    assert_eq!(k.as_tree().vistr().next().0, &10);
+
+    let k = CompleteTreeContainer::from_inorder(vec![5]).unwrap();
+    assert_eq!(*k.as_tree().root(), 5);
+}
+
+#[test]
+fn map_level_remaining_hint() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let hint = k.as_tree().vistr().level_remaining_hint();
+    let mapped = k.as_tree().vistr().map(|a| *a * 2);
+    assert_eq!(mapped.level_remaining_hint(), hint);
+    assert_eq!(hint, (3, Some(3)));
+
+    let it = mapped.dfs_preorder_iter();
+    assert_eq!(it.len(), 7);
+    assert_eq!(it.collect::<Vec<_>>(), &[6, 2, 0, 4, 10, 8, 12]);
+}
+
+mod level_remaining_hint {
+    use super::*;
+
+    fn tree(
+        height: usize,
+    ) -> compt::dfs_order::CompleteTreeContainer<usize, compt::dfs_order::PreOrder> {
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; compute_num_nodes(height)])
+            .unwrap()
+    }
+
+    //Checks the hint against the actual number of levels visited.
+    fn check<C: Visitor + Clone>(a: C, expected: (usize, Option<usize>)) {
+        assert_eq!(a.level_remaining_hint(), expected);
+        let levels = a
+            .with_depth(Depth(0))
+            .dfs_preorder_iter()
+            .map(|(d, _)| d.0 + 1)
+            .max()
+            .unwrap();
+        assert!(expected.0 <= levels);
+        if let Some(max) = expected.1 {
+            assert!(levels <= max);
+        }
+    }
+
+    #[test]
+    fn map() {
+        let k = tree(3);
+        check(k.as_tree().vistr().map(|a| a + 1), (3, Some(3)));
+    }
+
+    #[test]
+    fn flip() {
+        let k = tree(3);
+        check(k.as_tree().vistr().flip(), (3, Some(3)));
+    }
+
+    #[test]
+    fn take() {
+        let k = tree(4);
+        check(k.as_tree().vistr().take(0), (1, Some(1)));
+        check(k.as_tree().vistr().take(1), (2, Some(2)));
+        check(k.as_tree().vistr().take(5), (4, Some(4)));
+        check(Lopsided(5).take(2), (0, Some(3)));
+    }
+
+    #[test]
+    fn zip() {
+        let k1 = tree(3);
+        let k2 = tree(4);
+        check(k1.as_tree().vistr().zip(k2.as_tree().vistr()), (3, Some(3)));
+        check(k2.as_tree().vistr().zip(k1.as_tree().vistr()), (3, Some(3)));
+        check(k2.as_tree().vistr().zip(Lopsided(5)), (0, Some(4)));
+    }
+
+    #[test]
+    fn zip_pruned() {
+        let k = tree(4);
+
+        //The lower bound is only what both sides guarantee, and the upper bound
+        //is the tighter of the two known ones. Neither should over promise.
+        for (a, hint) in [
+            (Lopsided(2), (0, Some(4))),
+            (Lopsided(3), (0, Some(4))),
+            (Lopsided(6), (0, Some(4))),
+        ] {
+            let z = k.as_tree().vistr().zip(a.clone());
+            check(z.clone(), hint);
+            let (min_leaf, _) = z.leaf_depth_stats();
+            assert!(hint.0 <= min_leaf + 1);
+        }
+
+        check(k.as_tree().vistr().zip(Lopsided(6).take(1)), (0, Some(2)));
+        check(Lopsided(6).take(1).zip(k.as_tree().vistr()), (0, Some(2)));
+        check(k.as_tree().vistr().take(1).zip(Lopsided(6)), (0, Some(2)));
+    }
+
+    #[test]
+    fn level_iter() {
+        let k = tree(3);
+        check(k.as_tree().vistr().with_depth(Depth(0)), (3, Some(3)));
+    }
+
+    #[test]
+    fn nested() {
+        let k1 = tree(3);
+        let k2 = tree(4);
+        check(
+            k2.as_tree()
+                .vistr()
+                .flip()
+                .map(|a| a + 1)
+                .zip(k1.as_tree().vistr().with_depth(Depth(0)))
+                .take(1),
+            (2, Some(2)),
+        );
+        check(
+            k2.as_tree()
+                .vistr()
+                .take(2)
+                .flip()
+                .zip(k2.as_tree().vistr().map(|a| a + 1)),
+            (3, Some(3)),
+        );
+    }
+}
+
+#[test]
+fn collect_preorder_into() {
+    use core::mem::MaybeUninit;
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let mut buffer = [MaybeUninit::<i32>::uninit(); 7];
+    let res = k
+        .as_tree()
+        .vistr()
+        .map(|a| *a)
+        .collect_preorder_into(&mut buffer);
+    assert_eq!(res, &[3, 1, 0, 2, 5, 4, 6]);
+
+    let mut buffer = [MaybeUninit::<i32>::uninit(); 8];
+    let res = k
+        .as_tree()
+        .vistr()
+        .map(|a| *a)
+        .collect_preorder_into(&mut buffer);
+    assert_eq!(res, &[3, 1, 0, 2, 5, 4, 6]);
+}
+
+#[test]
+#[should_panic(expected = "buffer is too small")]
+fn collect_preorder_into_too_small() {
+    use core::mem::MaybeUninit;
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let mut buffer = [MaybeUninit::<i32>::uninit(); 6];
+    k.as_tree()
+        .vistr()
+        .map(|a| *a)
+        .collect_preorder_into(&mut buffer);
+}
+
+#[test]
+fn zip_mut() {
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    let mut k2 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 7]).unwrap();
+
+    k1.as_tree()
+        .vistr()
+        .zip_mut(k2.as_tree_mut().vistr_mut())
+        .dfs_preorder_iter()
+        .for_each(|(a, b)| *b = *a * 10);
+
+    assert_eq!(&*k2.into_nodes(), &[30, 10, 0, 20, 50, 40, 60]);
+}
+
+#[test]
+fn descend() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+
+    let target = 9;
+    let mut path = Vec::new();
+    let leaf = k.as_tree().vistr().descend(|&&a| {
+        path.push(a);
+        if target <= a {
+            Branch::Left
+        } else {
+            Branch::Right
+        }
+    });
+    assert_eq!(&path, &[7, 11, 9]);
+    assert_eq!(*leaf, 8);
+
+    assert_eq!(*k.as_tree().vistr().descend(|_| Branch::Right), 14);
+    assert_eq!(compt::leaf(3).descend(|_| unreachable!()), 3);
+}
+
+#[test]
+fn zip_take() {
+    let k1 = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..15).collect::<Vec<usize>>())
+            .unwrap();
+
+    let v = k1.as_tree().vistr().zip_take(k2.as_tree().vistr(), 2);
+    assert_eq!(v.level_remaining_hint(), (3, Some(3)));
+
+    let res: Vec<_> = v.dfs_preorder_iter().map(|(a, b)| (*a, *b)).collect();
+    assert_eq!(
+        &res,
+        &[(7, 0), (3, 1), (1, 2), (5, 5), (11, 8), (9, 9), (13, 12)]
+    );
+}
+
+#[test]
+fn map_stateful() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .map_stateful(0, |counter, a| {
+            let label = *counter;
+            *counter += 1;
+            (label, *a)
+        })
+        .dfs_preorder_iter()
+        .collect();
+    assert_eq!(
+        &res,
+        &[(0, 3), (1, 1), (2, 0), (3, 2), (4, 5), (5, 4), (6, 6)]
+    );
+
+    //The state follows the order next() is called, which is preorder even for an inorder traversal.
+    let mut res = Vec::new();
+    k.as_tree()
+        .vistr()
+        .map_stateful(0, |counter, a| {
+            *counter += 1;
+            (*counter, *a)
+        })
+        .dfs_inorder(|a| res.push(a));
+    assert_eq!(
+        &res,
+        &[(3, 0), (2, 1), (4, 2), (1, 3), (6, 4), (5, 5), (7, 6)]
+    );
+
+    //A bfs traversal calls next() level by level, so the state does too.
+    let mut res = Vec::new();
+    k.as_tree()
+        .vistr()
+        .map_stateful(0, |counter, a| {
+            *counter += 1;
+            (*counter, *a)
+        })
+        .bfs(|a| res.push(a));
+    assert_eq!(
+        &res,
+        &[(1, 3), (2, 1), (3, 5), (4, 0), (5, 2), (6, 4), (7, 6)]
+    );
+}
+
+#[test]
+fn dfs_preorder_iter_peek() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let mut it = k.as_tree().vistr().dfs_preorder_iter();
+    assert_eq!(it.peek().map(|a| **a), Some(3));
+    assert_eq!(it.peek().map(|a| **a), Some(3));
+    //A clone keeps the peeked element.
+    let res: Vec<_> = it.clone().copied().collect();
+    assert_eq!(&res, &[3, 1, 0, 2, 5, 4, 6]);
+    assert_eq!(it.len(), 7);
+    assert_eq!(it.next().copied(), Some(3));
+    assert_eq!(it.len(), 6);
+
+    let mut res = Vec::new();
+    while let Some(&&a) = it.peek() {
+        assert_eq!(it.next().copied(), Some(a));
+        res.push(a);
+    }
+    assert_eq!(&res, &[1, 0, 2, 5, 4, 6]);
+    assert!(it.peek().is_none());
+    assert!(it.next().is_none());
+}
+
+#[test]
+fn try_zip() {
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    let k2 = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2]).unwrap();
+
+    let err = k1
+        .as_tree()
+        .vistr()
+        .try_zip(k2.as_tree().vistr())
+        .err()
+        .unwrap();
+    assert_eq!((err.left, err.right), (3, 2));
+
+    let z = k1.as_tree().vistr().try_zip(k1.as_tree().vistr()).unwrap();
+    assert_eq!(z.dfs_preorder_iter().count(), 7);
+
+    //Unknown heights can't be compared.
+    assert!(k1.as_tree().vistr().try_zip(Lopsided(3)).is_ok());
+}
+
+#[test]
+fn checked_height() {
+    assert_eq!(compt::checked_height(0), Some(0));
+    assert_eq!(compt::checked_height(1), Some(1));
+    assert_eq!(compt::checked_height(7), Some(3));
+    assert_eq!(compt::compute_height(7), 3);
+    assert_eq!(
+        compt::checked_height(usize::MAX),
+        Some(usize::BITS as usize)
+    );
+
+    assert_eq!(compt::checked_height(2), None);
+    assert_eq!(compt::checked_height(5), None);
+    assert_eq!(compt::checked_height(8), None);
+}
+
+#[test]
+fn zip_children() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![1, 2, 3, 0, 1, 2, 3]).unwrap();
+
+    let z = k.as_tree().vistr().zip_children().unwrap();
+    assert!(z.dfs_preorder_iter().all(|(a, b)| a == b));
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![1, 2, 3, 0, 1, 2, 4]).unwrap();
+    let z = k.as_tree().vistr().zip_children().unwrap();
+    assert!(!z.dfs_preorder_iter().all(|(a, b)| a == b));
+
+    assert!(compt::leaf(0).zip_children().is_none());
+}
+
+#[test]
+fn root_to_leaf_paths() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res: Vec<Vec<_>> = k
+        .as_tree()
+        .vistr()
+        .root_to_leaf_paths()
+        .map(|path| path.into_iter().cloned().collect())
+        .collect();
+    assert_eq!(
+        res,
+        vec![vec![3, 1, 0], vec![3, 1, 2], vec![3, 5, 4], vec![3, 5, 6]]
+    );
+
+    let res: Vec<_> = compt::leaf(7).root_to_leaf_paths().collect();
+    assert_eq!(res, vec![vec![7]]);
+}
+
+#[test]
+fn map_keyed() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let v = k.as_tree().vistr().map_keyed(|a| **a * **a);
+    assert_eq!(v.level_remaining_hint(), (3, Some(3)));
+
+    let it = v.dfs_preorder_iter();
+    assert_eq!(it.len(), 7);
+    let res: Vec<_> = it.map(|(a, b)| (*a, b)).collect();
+    assert_eq!(
+        &res,
+        &[(3, 9), (1, 1), (0, 0), (2, 4), (5, 25), (4, 16), (6, 36)]
+    );
+}
+
+#[test]
+fn is_bst() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 2, 4, 5, 6]).unwrap();
+    assert!(k.as_tree().is_bst());
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 7, 5, 6]).unwrap();
+    assert!(!k.as_tree().is_bst());
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![3, 1, 0, 2, 5, 4, 6]).unwrap();
+    assert!(k.as_tree().is_bst());
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    assert!(!k.as_tree().is_bst());
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_postorder(vec![0, 2, 1, 4, 6, 5, 3]).unwrap();
+    assert!(k.as_tree().is_bst());
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_postorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    assert!(!k.as_tree().is_bst());
+}
+
+#[test]
+fn iter_ordered() {
+    //       0
+    //   1       2
+    // 3   4   5    6
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![3, 1, 4, 0, 5, 2, 6]).unwrap();
+
+    let expected = [
+        (TraversalOrder::PreOrder, [0, 1, 3, 4, 2, 5, 6]),
+        (TraversalOrder::InOrder, [3, 1, 4, 0, 5, 2, 6]),
+        (TraversalOrder::PostOrder, [3, 4, 1, 5, 6, 2, 0]),
+        (TraversalOrder::BreadthFirst, [0, 1, 2, 3, 4, 5, 6]),
+    ];
+    for (order, expected) in expected.iter() {
+        let res: Vec<_> = k.as_tree().vistr().iter_ordered(*order).cloned().collect();
+        assert_eq!(&res, expected);
+    }
+}
+
+#[test]
+fn map_leaves() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .map(|a| *a)
+        .map_leaves(|a| a * 2)
+        .dfs_inorder_iter()
+        .collect();
+    assert_eq!(&res, &[0, 1, 4, 3, 8, 5, 12]);
+}
+
+#[test]
+fn dfs_preorder_bounded() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+
+    let mut res = Vec::new();
+    k.as_tree()
+        .vistr()
+        .dfs_preorder_bounded(1, |a| res.push(*a));
+    assert_eq!(&res, &[7, 3, 11]);
+
+    let mut res = Vec::new();
+    k.as_tree()
+        .vistr()
+        .dfs_preorder_bounded(10, |a| res.push(*a));
+    assert_eq!(res.len(), 15);
+
+    let mut res = Vec::new();
+    Lopsided(1_000_000).dfs_preorder_bounded(2, |a| res.push(a));
+    assert_eq!(&res, &[1_000_000, 0, 999_999, 0, 999_998]);
+}
+
+#[test]
+fn container_ord() {
+    let a =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    let b = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 2, 1]).unwrap();
+    let c = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![9, 2, 0]).unwrap();
+
+    //Preorders are [3,1,0,2,5,4,6], [2,0,1] and [2,9,0].
+    let mut trees = vec![a.clone(), b.clone(), c.clone()];
+    trees.sort();
+    assert!(trees == vec![b.clone(), c.clone(), a.clone()]);
+
+    assert!(b < c);
+    assert!(c < a);
+    assert!(a == a.clone());
+    assert!(a != b);
+}
+
+#[test]
+fn every_k_levels() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .every_k_levels(2)
+        .map(|(d, a)| (d.0, *a))
+        .collect();
+    assert_eq!(&res, &[(0, 7), (2, 1), (2, 5), (2, 9), (2, 13)]);
+
+    assert_eq!(k.as_tree().vistr().every_k_levels(1).count(), 15);
+}
+
+#[test]
+fn group_by_depth() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![3, 1, 4, 0, 5, 2, 6]).unwrap();
+
+    let map = k.as_tree().vistr().map(|a| *a).group_by_depth();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map[&0], vec![0]);
+    assert_eq!(map[&1], vec![1, 2]);
+    assert_eq!(map[&2], vec![3, 4, 5, 6]);
+}
+
+#[test]
+fn zip_different_heights_exact_size() {
+    let k1 = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2]).unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    let z = || k1.as_tree().vistr().zip(k2.as_tree().vistr());
+    let z2 = || k2.as_tree().vistr().zip(k1.as_tree().vistr());
+    assert_eq!(z().level_remaining_hint(), (2, Some(2)));
+    assert_eq!(z2().level_remaining_hint(), (2, Some(2)));
+
+    fn check<I: ExactSizeIterator>(mut it: I) {
+        let mut len = it.len();
+        assert_eq!(len, 3);
+        while it.next().is_some() {
+            assert_eq!(it.size_hint(), (len - 1, Some(len - 1)));
+            len -= 1;
+            assert_eq!(it.len(), len);
+        }
+        assert_eq!(len, 0);
+    }
+    check(z().dfs_preorder_iter());
+    check(z().dfs_inorder_iter());
+    check(z().dfs_inorder_rev_iter());
+    check(z2().dfs_preorder_iter());
+    check(z2().dfs_inorder_iter());
+
+    let res: Vec<_> = z2().dfs_inorder_iter().map(|(a, b)| (*a, *b)).collect();
+    assert_eq!(&res, &[(1, 0), (3, 1), (5, 2)]);
+}
+
+#[test]
+fn dfs_preorder_iter_well_formed() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<usize>>())
+        .unwrap();
+    assert_eq!(k.as_tree().vistr().dfs_preorder_iter().count(), 15);
+    assert_eq!(Lopsided(3).dfs_preorder_iter().count(), 7);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "level_remaining_hint")]
+fn dfs_preorder_iter_malformed() {
+    //Claims to be a single node but has children.
+    #[derive(Clone)]
+    struct Liar(usize);
+    impl Visitor for Liar {
+        type Item = usize;
+        fn next(self) -> (Self::Item, Option<[Self; 2]>) {
+            if self.0 == 0 {
+                (0, None)
+            } else {
+                (self.0, Some([Liar(self.0 - 1), Liar(self.0 - 1)]))
+            }
+        }
+        fn level_remaining_hint(&self) -> (usize, Option<usize>) {
+            (1, Some(1))
+        }
+    }
+    Liar(2).dfs_preorder_iter().count();
+}
+
+#[test]
+fn reduce_depth() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+
+    //Weight each value by its depth plus one.
+    let sum = k.as_tree().vistr().reduce_depth(
+        Depth(0),
+        |d, a| *a * (d.0 + 1),
+        |d, a, left, right| *a * (d.0 + 1) + left + right,
+    );
+    assert_eq!(sum, 3 + (1 + 5) * 2 + (2 + 4 + 6) * 3);
+
+    assert_eq!(
+        compt::leaf(4).reduce_depth(Depth(2), |d, a| a * d.0, |_, _, _, _| unreachable!()),
+        8
+    );
+}
+
+#[test]
+fn tree_builder() {
+    use compt::dfs_order::*;
+    let builder = || {
+        let mut b = TreeBuilder::new();
+        b.push_level(vec![0]);
+        b.push_level(vec![1, 2]);
+        b.push_level(3..7);
+        b
+    };
+
+    let k: CompleteTreeContainer<_, InOrder> = builder().build().unwrap();
+    assert_eq!(&*k.into_nodes(), &[3, 1, 4, 0, 5, 2, 6]);
+    let k: CompleteTreeContainer<_, PreOrder> = builder().build().unwrap();
+    assert_eq!(&*k.into_nodes(), &[0, 1, 3, 4, 2, 5, 6]);
+    let k: CompleteTreeContainer<_, PostOrder> = builder().build().unwrap();
+    assert_eq!(&*k.into_nodes(), &[3, 4, 1, 5, 6, 2, 0]);
+
+    let mut b = TreeBuilder::new();
+    b.push_level(vec![0]);
+    b.push_level(vec![1, 2, 3]);
+    assert_eq!(
+        b.build::<InOrder>().err(),
+        Some(BuildError::WrongLevelSize {
+            depth: 1,
+            expected: 2,
+            found: 3
+        })
+    );
+
+    assert_eq!(
+        TreeBuilder::<usize>::new().build::<InOrder>().err(),
+        Some(BuildError::NoLevels)
+    );
+}
+
+#[test]
+fn leaf_depth_stats() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 15]).unwrap();
+    assert_eq!(k.as_tree().vistr().leaf_depth_stats(), (3, 3));
+
+    assert_eq!(Lopsided(0).leaf_depth_stats(), (0, 0));
+    assert_eq!(Lopsided(3).leaf_depth_stats(), (1, 3));
+}
+
+#[test]
+fn into_vistr() {
+    use compt::dfs_order::*;
+    let names = |v: &[usize]| v.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+    let k = CompleteTreeContainer::from_preorder(names(&[0, 1, 2, 3, 4, 5, 6])).unwrap();
+    let v = k.into_vistr();
+    assert_eq!(v.level_remaining_hint(), (3, Some(3)));
+    let res: Vec<String> = v.dfs_preorder_iter().collect();
+    assert_eq!(res, names(&[0, 1, 2, 3, 4, 5, 6]));
+
+    let k = CompleteTreeContainer::from_inorder(names(&[0, 1, 2, 3, 4, 5, 6])).unwrap();
+    let res: Vec<String> = k.into_vistr().dfs_preorder_iter().collect();
+    assert_eq!(res, names(&[3, 1, 0, 2, 5, 4, 6]));
+
+    let k = CompleteTreeContainer::from_postorder(names(&[0, 1, 2, 3, 4, 5, 6])).unwrap();
+    let res: Vec<String> = k.into_vistr().dfs_preorder_iter().collect();
+    assert_eq!(res, names(&[6, 2, 0, 1, 5, 3, 4]));
+
+    //Subtrees can be visited in any order.
+    let k = CompleteTreeContainer::from_preorder(names(&[0, 1, 2, 3, 4, 5, 6])).unwrap();
+    let res: Vec<String> = k.into_vistr().flip().dfs_preorder_iter().collect();
+    assert_eq!(res, names(&[0, 4, 6, 5, 1, 3, 2]));
+
+    //Elements that are never yielded are still dropped.
+    let counter = std::rc::Rc::new(());
+    let k = CompleteTreeContainer::from_inorder(vec![counter.clone(); 7]).unwrap();
+    let mut it = k.into_vistr().dfs_preorder_iter();
+    it.next();
+    it.next();
+    assert_eq!(std::rc::Rc::strong_count(&counter), 6);
+    drop(it);
+    assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+
+    //The children own disjoint elements, so they can be traversed on other threads.
+    let k = CompleteTreeContainer::from_inorder(names(&[0, 1, 2, 3, 4, 5, 6])).unwrap();
+    let (root, rest) = k.into_vistr().next();
+    let [left, right] = rest.unwrap();
+    let handle = std::thread::spawn(move || left.dfs_preorder_iter().collect::<Vec<_>>());
+    let right: Vec<String> = right.dfs_preorder_iter().collect();
+    assert_eq!(root, "3");
+    assert_eq!(handle.join().unwrap(), names(&[1, 0, 2]));
+    assert_eq!(right, names(&[5, 4, 6]));
+
+    //Zero sized elements.
+    let k = CompleteTreeContainer::from_preorder(vec![(); 7]).unwrap();
+    assert_eq!(k.into_vistr().dfs_preorder_iter().count(), 7);
+}
+
+#[test]
+fn merge_into() {
+    use compt::dfs_order::*;
+    let mut k1 = CompleteTreeContainer::from_preorder(vec![String::new(); 7]).unwrap();
+    let k2 =
+        CompleteTreeContainer::from_preorder((0..7).map(|a| a.to_string()).collect::<Vec<_>>())
+            .unwrap();
+
+    k1.as_tree_mut()
+        .vistr_mut()
+        .merge_into(k2.into_vistr(), |a, b| *a = b);
+
+    assert_eq!(
+        k1.as_tree()
+            .vistr()
+            .dfs_preorder_iter()
+            .cloned()
+            .collect::<Vec<_>>(),
+        ["0", "1", "2", "3", "4", "5", "6"]
+    );
+}
+
+#[test]
+fn adjacency_list() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0, 1, 2]).unwrap();
+    assert_eq!(
+        compt::adjacency_list(k.as_tree().get_height()),
+        vec![(0, Some(1), Some(2)), (1, None, None), (2, None, None)]
+    );
+    assert_eq!(compt::adjacency_list(1), vec![(0, None, None)]);
+    assert!(compt::adjacency_list(0).is_empty());
+}
+
+#[test]
+fn child_sizes() {
+    let mut k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0; 15]).unwrap();
+    assert_eq!(k.as_tree().vistr().child_sizes(), Some((7, 7)));
+
+    let v = k.as_tree_mut().vistr_mut();
+    assert_eq!(v.child_sizes(), Some((7, 7)));
+    let (_, rest) = v.next();
+    let [left, _] = rest.unwrap();
+    assert_eq!(left.child_sizes(), Some((3, 3)));
+
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0]).unwrap();
+    assert_eq!(k.as_tree().vistr().child_sizes(), None);
+}
+
+#[test]
+fn flip_at() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .flip_at(|d| d.0 % 2 == 1)
+        .dfs_preorder_iter()
+        .copied()
+        .collect();
+    assert_eq!(res, [0, 1, 3, 2, 4, 6, 5]);
+
+    //Flipping at every depth is the same as flip().
+    let a: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .flip_at(|_| true)
+        .dfs_preorder_iter()
+        .collect();
+    let b: Vec<_> = k.as_tree().vistr().flip().dfs_preorder_iter().collect();
+    assert_eq!(a, b);
+
+    let mut res = Vec::new();
+    k.as_tree()
+        .vistr()
+        .flip_at(|d| d.0 == 0)
+        .bfs(|a| res.push(*a));
+    assert_eq!(res, [0, 4, 1, 5, 6, 2, 3]);
+}
+
+#[test]
+fn edges() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder((0..7).collect::<Vec<_>>()).unwrap();
+    let res: Vec<_> = k.as_tree().vistr().edges().map(|(a, b)| (*a, *b)).collect();
+    assert_eq!(res.len(), 6);
+    assert_eq!(res, [(3, 1), (1, 0), (1, 2), (3, 5), (5, 4), (5, 6)]);
+
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0]).unwrap();
+    assert_eq!(k.as_tree().vistr().edges().count(), 0);
+}
+
+#[test]
+fn try_postorder() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_postorder((0..15).collect::<Vec<_>>())
+        .unwrap();
+
+    let mut res = Vec::new();
+    let ret = k.as_tree().vistr().try_postorder(|a| {
+        if *a == 5 {
+            return Err(*a);
+        }
+        res.push(*a);
+        Ok(())
+    });
+    assert_eq!(ret, Err(5));
+    assert_eq!(res, [0, 1, 2, 3, 4]);
+
+    let mut res = Vec::new();
+    let ret: Result<(), ()> = k.as_tree().vistr().try_postorder(|a| {
+        res.push(*a);
+        Ok(())
+    });
+    assert_eq!(ret, Ok(()));
+    assert_eq!(res, (0..15).collect::<Vec<_>>());
+}
+
+#[test]
+fn depth_ops() {
+    assert!(Depth(3) > Depth(2));
+    assert_eq!(Depth(2).incr(), Depth(3));
+    assert_eq!(Depth::from(4), Depth(4));
+    let a: usize = Depth(5).into();
+    assert_eq!(a, 5);
+
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let deep: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .with_depth(Depth(0))
+        .dfs_preorder_iter()
+        .filter(|(depth, _)| *depth >= Depth(2))
+        .map(|(_, a)| *a)
+        .collect();
+    assert_eq!(deep, [2, 3, 5, 6]);
+}
+
+#[test]
+fn scatter_into_bfs() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder((0..7).collect::<Vec<_>>()).unwrap();
+    let mut out = [0; 7];
+    k.as_tree().vistr().scatter_into_bfs(&mut out, |a| *a * 10);
+    assert_eq!(out, [30, 10, 50, 0, 20, 40, 60]);
+}
+
+#[test]
+#[should_panic]
+fn scatter_into_bfs_wrong_len() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder((0..7).collect::<Vec<_>>()).unwrap();
+    let mut out = [0; 6];
+    k.as_tree().vistr().scatter_into_bfs(&mut out, |a| *a);
+}
+
+#[test]
+fn iter_layout() {
+    use compt::dfs_order::*;
+    let nodes: Vec<usize> = (0..15).collect();
+
+    let k = CompleteTreeContainer::from_preorder(nodes.clone()).unwrap();
+    let fast: Vec<_> = k.as_tree().vistr().iter_layout().collect();
+    let slow: Vec<_> = k.as_tree().vistr().dfs_preorder_iter().collect();
+    assert_eq!(fast, slow);
+
+    let k = CompleteTreeContainer::from_inorder(nodes.clone()).unwrap();
+    let fast: Vec<_> = k.as_tree().vistr().iter_layout().collect();
+    let slow: Vec<_> = k.as_tree().vistr().dfs_inorder_iter().collect();
+    assert_eq!(fast, slow);
+
+    let mut k = CompleteTreeContainer::from_postorder(nodes.clone()).unwrap();
+    let fast: Vec<_> = k.as_tree().vistr().iter_layout().collect();
+    let slow: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .iter_ordered(TraversalOrder::PostOrder)
+        .collect();
+    assert_eq!(fast, slow);
+
+    //Also works on a subtree.
+    let (_, rest) = k.as_tree_mut().vistr_mut().next();
+    let [_, right] = rest.unwrap();
+    for a in right.iter_layout_mut() {
+        *a = 0;
+    }
+    assert_eq!(&k.as_tree().get_nodes()[7..14], &[0; 7]);
+    assert_eq!(k.as_tree().get_nodes()[14], 14);
+
+    //The Visitor methods are not shadowed, so the preorder iterator can still peek.
+    let k = CompleteTreeContainer::from_preorder(nodes).unwrap();
+    let mut it = k.as_tree().vistr().dfs_preorder_iter();
+    assert_eq!(it.peek(), Some(&&0));
+}
+
+#[test]
+fn count_preorder() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_inorder((0..15).collect::<Vec<_>>()).unwrap();
+    assert_eq!(k.as_tree().vistr().count_preorder(|a| *a % 2 == 0), 8);
+    assert_eq!(k.as_tree().vistr().count_preorder(|_| false), 0);
+}
+
+#[test]
+fn reborrow() {
+    fn count<V: BorrowableVisitor>(v: &mut V) -> usize {
+        let mut total = 0;
+        v.reborrow().dfs_preorder(|_| total += 1);
+        total
+    }
+
+    let mut k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![1usize; 7]).unwrap();
+    let mut v = k.as_tree_mut().vistr_mut();
+
+    //Read pass.
+    assert_eq!(count(&mut v), 7);
+    let mut total = 0;
+    v.reborrow().dfs_preorder(|a| total += *a);
+    assert_eq!(total, 7);
+
+    //Write pass, through another reborrow.
+    v.reborrow().dfs_preorder(|a| *a = 2);
+
+    //The original visitor is still usable.
+    v.dfs_preorder(|a| *a += 1);
+    assert_eq!(&*k.into_nodes(), &[3; 7]);
+}
+
+#[test]
+fn map_collect_errors() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let (oks, errs) =
+        k.as_tree()
+            .vistr()
+            .map_collect_errors(|a| if *a % 3 == 0 { Err(*a) } else { Ok(*a * 10) });
+    assert_eq!(oks, [10, 20, 40, 50]);
+    assert_eq!(errs, [0, 3, 6]);
+}
+
+#[test]
+fn zip_three_trees() {
+    use compt::dfs_order::*;
+    let mut a = CompleteTreeContainer::from_preorder(vec![0; 7]).unwrap();
+    let b = CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let c = CompleteTreeContainer::from_preorder((10..17).collect::<Vec<_>>()).unwrap();
+
+    a.as_tree_mut()
+        .vistr_mut()
+        .zip(b.as_tree().vistr().zip(c.as_tree().vistr()))
+        .dfs_preorder(|(a, (b, c))| *a = b + c);
+
+    assert_eq!(&*a.into_nodes(), &[10, 12, 14, 16, 18, 20, 22]);
+}
+
+#[test]
+fn into_dyn() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<i32>>())
+        .unwrap();
+
+    let visitors: Vec<Box<dyn DynVisitor<Item = i32>>> = vec![
+        k.as_tree().vistr().map(|a| *a).into_dyn(),
+        Lopsided(2).map(|a| a as i32 * 10).into_dyn(),
+    ];
+
+    let res: Vec<Vec<i32>> = visitors
+        .into_iter()
+        .map(|v| {
+            assert!(v.level_remaining_hint().0 <= 3);
+            v.dfs_preorder_iter().collect()
+        })
+        .collect();
+    assert_eq!(res[0], [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(res[1], [20, 0, 10, 0, 0]);
+}
+
+#[test]
+fn find_duplicate_subtrees() {
+    use Branch::*;
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 1, 2, 3]).unwrap();
+    let res = k.as_tree().vistr().find_duplicate_subtrees();
+    assert_eq!(
+        res,
+        vec![
+            vec![vec![Left, Left], vec![Right, Left]],
+            vec![vec![Left, Right], vec![Right, Right]],
+            vec![vec![Left], vec![Right]],
+        ]
+    );
+
+    //Same elements but different children are not duplicates.
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 1, 3, 2]).unwrap();
+    let res = k.as_tree().vistr().find_duplicate_subtrees();
+    assert_eq!(
+        res,
+        vec![
+            vec![vec![Left, Left], vec![Right, Right]],
+            vec![vec![Left, Right], vec![Right, Left]],
+        ]
+    );
+}
+
+#[test]
+fn is_mirror() {
+    use compt::dfs_order::*;
+    let k = CompleteTreeContainer::from_inorder(vec![3, 2, 1, 0, 1, 2, 3]).unwrap();
+    assert!(k.as_tree().vistr().is_mirror());
+
+    let k = CompleteTreeContainer::from_inorder(vec![3, 2, 1, 0, 3, 2, 1]).unwrap();
+    assert!(!k.as_tree().vistr().is_mirror());
+
+    let k = CompleteTreeContainer::from_inorder(vec![0]).unwrap();
+    assert!(k.as_tree().vistr().is_mirror());
+
+    //Same elements, different shapes.
+    assert!(!Lopsided(2).map(|_| 0).is_mirror());
+}
+
+#[test]
+fn zip_cmp() {
+    use core::cmp::Ordering::*;
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 4, 5, 6]).unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 1, 3, 5, 5, 6]).unwrap();
+    let res: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .zip_cmp(k2.as_tree().vistr())
+        .dfs_preorder_iter()
+        .collect();
+    assert_eq!(res, [Equal, Equal, Greater, Equal, Less, Equal, Equal]);
+}
+
+#[test]
+fn fill_preorder() {
+    let mut k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0; 7]).unwrap();
+    k.as_tree_mut().vistr_mut().fill_preorder(0..7);
+    assert_eq!(k.as_tree().get_nodes(), &[2, 1, 3, 0, 5, 4, 6]);
+}
+
+#[test]
+#[should_panic]
+fn fill_preorder_too_short() {
+    let mut k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0; 7]).unwrap();
+    k.as_tree_mut().vistr_mut().fill_preorder(0..6);
+}
+
+#[test]
+fn dfs_postorder_iter_with_depth() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_postorder((0..7).collect::<Vec<_>>())
+        .unwrap();
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .dfs_postorder_iter_with_depth()
+        .map(|(d, a)| (d.0, *a))
+        .collect();
+    assert_eq!(
+        res,
+        [(2, 0), (2, 1), (1, 2), (2, 3), (2, 4), (1, 5), (0, 6)]
+    );
+}
+
+#[test]
+fn into_root_and_children() {
+    use compt::dfs_order::*;
+    fn check(k: CompleteTreeContainer<usize, InOrder>, expected: &[usize]) {
+        assert_eq!(k.as_tree().get_nodes(), expected);
+        let mid = expected.len() / 2;
+        let (root, children) = k.into_root_and_children();
+        assert_eq!(root, expected[mid]);
+        match children {
+            Some((left, right)) => {
+                check(left, &expected[..mid]);
+                check(right, &expected[mid + 1..]);
+            }
+            None => assert_eq!(expected.len(), 1),
+        }
+    }
+
+    let nodes: Vec<_> = (0..7).collect();
+    check(
+        CompleteTreeContainer::from_inorder(nodes.clone()).unwrap(),
+        &nodes,
+    );
+
+    let k = CompleteTreeContainer::from_preorder(nodes).unwrap();
+    let (root, children) = k.into_root_and_children();
+    let (left, right) = children.unwrap();
+    assert_eq!(root, 0);
+    assert_eq!(left.as_tree().get_nodes(), &[1, 2, 3]);
+    assert_eq!(right.as_tree().get_nodes(), &[4, 5, 6]);
+}
+
+#[test]
+fn count_leaves_and_internal() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 7]).unwrap();
+    assert_eq!(k.as_tree().vistr().count_leaves(), 4);
+    assert_eq!(k.as_tree().vistr().count_internal(), 3);
+
+    assert_eq!(k.as_tree().vistr().take(1).count_leaves(), 2);
+    assert_eq!(k.as_tree().vistr().take(1).count_internal(), 1);
+
+    //No exact hint, so these walk the tree.
+    assert_eq!(Lopsided(3).count_leaves(), 4);
+    assert_eq!(Lopsided(3).count_internal(), 3);
+    assert_eq!(Lopsided(5).take(2).count_leaves(), 3);
+    assert_eq!(Lopsided(5).take(2).count_internal(), 2);
+}
+
+#[test]
+fn map_index() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![0; 7]).unwrap();
+    let v = k.as_tree().vistr().map_index(|i, _| i);
+    assert_eq!(v.get_height(), 3);
+    let res: Vec<_> = v.dfs_preorder_iter().collect();
+    assert_eq!(res, [0, 1, 3, 4, 2, 5, 6]);
+}
+
+#[test]
+fn collect_container() {
+    use compt::dfs_order::*;
+    let k = CompleteTreeContainer::from_inorder((0..15).collect::<Vec<_>>()).unwrap();
+
+    let pre: CompleteTreeContainer<usize, PreOrder> =
+        k.as_tree().vistr().map(|a| *a).collect_container().unwrap();
+    let a: Vec<_> = k.as_tree().vistr().dfs_preorder_iter().copied().collect();
+    assert_eq!(pre.as_tree().get_nodes(), &a[..]);
+
+    let post: CompleteTreeContainer<usize, PostOrder> = pre
+        .as_tree()
+        .vistr()
+        .map(|a| *a)
+        .collect_container()
+        .unwrap();
+    let back: CompleteTreeContainer<usize, InOrder> = post
+        .as_tree()
+        .vistr()
+        .map(|a| *a)
+        .collect_container()
+        .unwrap();
+    assert!(back == k);
+
+    //Lopsided(3) has 7 nodes but is not a complete tree.
+    match Lopsided(3).collect_container::<InOrder>() {
+        Ok(_) => panic!("expected an error"),
+        Err(e) => assert_eq!(e.length, 7),
+    }
+}
+
+#[test]
+fn is_height_balanced() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 15]).unwrap();
+    assert!(k.as_tree().vistr().is_height_balanced());
+
+    assert!(Lopsided(1).is_height_balanced());
+    assert!(Lopsided(2).is_height_balanced());
+    assert!(!Lopsided(3).is_height_balanced());
+}
+
+#[test]
+fn zip_into_container() {
+    use compt::dfs_order::*;
+    let k1 = CompleteTreeContainer::from_preorder((0..7).collect::<Vec<i32>>()).unwrap();
+    let k2 = CompleteTreeContainer::from_preorder((10..17).collect::<Vec<i32>>()).unwrap();
+
+    let res: CompleteTreeContainer<i32, InOrder> = k1
+        .as_tree()
+        .vistr()
+        .zip_into_container(k2.as_tree().vistr(), |a, b| a + b)
+        .unwrap();
+    assert_eq!(res.as_tree().get_nodes(), &[14, 12, 16, 10, 20, 18, 22]);
+
+    let k3 = CompleteTreeContainer::from_preorder(vec![0i32; 3]).unwrap();
+    let res = k1
+        .as_tree()
+        .vistr()
+        .zip_into_container::<_, _, InOrder>(k3.as_tree().vistr(), |a, b| a + b);
+    match res {
+        Err(compt::ShapeMismatch { left, right }) => assert_eq!((left, right), (3, 2)),
+        Ok(_) => panic!("expected a shape mismatch"),
+    }
+}
+
+#[test]
+fn by_ref_items() {
+    use compt::dfs_order::*;
+    fn sum<'a, V: ByRefVisitor>(v: V) -> usize
+    where
+        V::ByRef: Visitor<Item = &'a usize>,
+    {
+        v.by_ref_items().dfs_preorder_iter().sum()
+    }
+
+    let mut k = CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let v = k.as_tree_mut().vistr_mut();
+    let (_, rest) = v.next();
+    let [left, right] = rest.unwrap();
+
+    //Keep reading the left subtree while writing to the right one.
+    let left = left.by_ref_items();
+    right.dfs_preorder(|a| *a = 0);
+    let res: Vec<_> = left.dfs_preorder_iter().copied().collect();
+    assert_eq!(res, [1, 2, 3]);
+    assert_eq!(&k.as_tree().get_nodes()[4..], &[0, 0, 0]);
+
+    assert_eq!(sum(k.as_tree_mut().vistr_mut()), 6);
+
+    let mut k = CompleteTreeContainer::from_inorder((0..7).collect::<Vec<_>>()).unwrap();
+    assert_eq!(sum(k.as_tree_mut().vistr_mut()), 21);
+}
+
+#[test]
+fn expand_by_level() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .expand_by_level(|d, a| vec![*a; d.0 + 1])
+        .collect();
+    assert_eq!(res.len(), 1 + 2 * 2 + 4 * 3);
+    assert_eq!(&res[..5], &[0, 1, 1, 2, 2]);
+
+    //Nodes can also produce nothing.
+    let res: Vec<_> = k
+        .as_tree()
+        .vistr()
+        .expand_by_level(|d, a| if d.0 == 1 { vec![*a] } else { vec![] })
+        .collect();
+    assert_eq!(res, [1, 4]);
+}
+
+#[test]
+fn diff() {
+    use Branch::*;
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let k2 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2, 3, 4, 9, 6]).unwrap();
+
+    let res: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .diff(k2.as_tree().vistr())
+        .map(|(path, a, b)| (path, *a, *b))
+        .collect();
+    assert_eq!(res, vec![(vec![Right, Left], 5, 9)]);
+
+    assert_eq!(k1.as_tree().vistr().diff(k1.as_tree().vistr()).count(), 0);
+
+    //Lopsided(1) is a complete tree of height 2, but Lopsided(2) goes deeper on the right.
+    let res: Vec<_> = Lopsided(1)
+        .map(|_| 0)
+        .diff(Lopsided(2).map(|_| 0))
+        .map(|(path, _, _)| path)
+        .collect();
+    assert_eq!(res, vec![vec![Right]]);
+}
+
+#[test]
+fn subtrees_at_depth() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder((0..15).collect::<Vec<_>>())
+        .unwrap();
+    let res: Vec<Vec<_>> = k
+        .as_tree()
+        .vistr()
+        .subtrees_at_depth(2)
+        .map(|v| v.dfs_preorder_iter().copied().collect())
+        .collect();
+    assert_eq!(
+        res,
+        vec![
+            vec![2, 3, 4],
+            vec![5, 6, 7],
+            vec![9, 10, 11],
+            vec![12, 13, 14]
+        ]
+    );
+
+    assert_eq!(k.as_tree().vistr().subtrees_at_depth(0).count(), 1);
+    assert_eq!(k.as_tree().vistr().subtrees_at_depth(3).count(), 8);
+    assert_eq!(k.as_tree().vistr().subtrees_at_depth(4).count(), 0);
+    assert_eq!(Lopsided(3).subtrees_at_depth(2).count(), 2);
+}
+
+#[test]
+fn to_newick() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![
+        "A", "C", "B", "G", "D", "F", "E",
+    ])
+    .unwrap();
+    assert_eq!(k.as_tree().vistr().to_newick(), "((A,B)C,(D,E)F)G;");
+
+    let k = compt::dfs_order::CompleteTreeContainer::from_inorder(vec![5]).unwrap();
+    assert_eq!(k.as_tree().vistr().to_newick(), "5;");
+}
+
+#[test]
+fn zip_pad() {
+    let k1 = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0, 1, 2]).unwrap();
+    let k2 = compt::dfs_order::CompleteTreeContainer::from_preorder((10..17).collect::<Vec<_>>())
+        .unwrap();
+
+    let v = k1.as_tree().vistr().zip_pad(k2.as_tree().vistr());
+    assert_eq!(v.level_remaining_hint(), (3, Some(3)));
+    let res: Vec<_> = v
+        .dfs_preorder_iter()
+        .map(|(a, b)| (a.copied(), b.copied()))
+        .collect();
+    assert_eq!(
+        res,
+        [
+            (Some(0), Some(10)),
+            (Some(1), Some(11)),
+            (None, Some(12)),
+            (None, Some(13)),
+            (Some(2), Some(14)),
+            (None, Some(15)),
+            (None, Some(16)),
+        ]
+    );
+
+    //The order of the trees does not matter.
+    let res: Vec<_> = k2
+        .as_tree()
+        .vistr()
+        .zip_pad(k1.as_tree().vistr())
+        .dfs_preorder_iter()
+        .collect();
+    assert_eq!(res.len(), 7);
+    assert_eq!(res[2], (Some(&12), None));
+}
+
+#[test]
+fn paths_to() {
+    use Branch::*;
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let res = k.as_tree().vistr().paths_to(|a| *a % 2 == 0);
+    assert_eq!(
+        res,
+        vec![vec![], vec![Left, Left], vec![Right], vec![Right, Right]]
+    );
+
+    //The paths can be used to find the nodes again.
+    let mut k = k;
+    k.as_tree_mut().swap_subtrees(&res[2]);
+    assert_eq!(k.as_tree().get_nodes(), &[0, 1, 2, 3, 4, 6, 5]);
+}
+
+#[test]
+fn map_identity_adds_no_state() {
+    use core::mem::size_of_val;
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder((0..15).collect::<Vec<_>>())
+        .unwrap();
+
+    let v = k.as_tree().vistr();
+    let m = k.as_tree().vistr().map(|a| a);
+    assert_eq!(size_of_val(&v), size_of_val(&m));
+
+    let mut a = Vec::new();
+    v.dfs_preorder(|x| a.push(*x));
+    let mut b = Vec::new();
+    m.dfs_preorder(|x| b.push(*x));
+    assert_eq!(a, b);
+}
+
+#[test]
+fn dfs_preorder_leaf_flagged() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let it = k.as_tree().vistr().dfs_preorder_leaf_flagged();
+    assert_eq!(it.len(), 7);
+    let leaves: Vec<_> = it.filter(|(_, leaf)| *leaf).map(|(a, _)| *a).collect();
+    assert_eq!(leaves, [2, 3, 5, 6]);
+}
+
+#[test]
+fn fold_levels() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let res = k.as_tree().vistr().fold_levels(0, |acc, a| acc + a);
+    assert_eq!(res, [0, 1 + 4, 2 + 3 + 5 + 6]);
+
+    let res = Lopsided(2).fold_levels(Vec::new(), |mut acc, a| {
+        acc.push(a);
+        acc
+    });
+    assert_eq!(res, [vec![2], vec![0, 1], vec![0, 0]]);
+}
+
+#[test]
+fn split_by_level_parity() {
+    let k =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let (even, odd) = k.as_tree().vistr().split_by_level_parity();
+    assert_eq!(even, [&0, &2, &3, &5, &6]);
+    assert_eq!(odd, [&1, &4]);
+}
+
+#[test]
+fn unfold() {
+    //Seeds are (bfs index, depth).
+    let v = compt::unfold((0, 0), |(i, depth): (usize, usize)| {
+        let children = if depth < 2 {
+            Some(((2 * i + 1, depth + 1), (2 * i + 2, depth + 1)))
+        } else {
+            None
+        };
+        (i, children)
+    });
+    let res: Vec<_> = v.clone().dfs_preorder_iter().collect();
+    assert_eq!(res, [0, 1, 3, 4, 2, 5, 6]);
+    assert!(v.is_perfect());
+}
+
+#[test]
+fn cache() {
+    use compt::dfs_order::*;
+    let k = CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let cached: CompleteTreeContainer<String, PreOrder> =
+        k.as_tree().vistr().map(|a| a.to_string()).cache().unwrap();
+
+    let pre: Vec<_> = cached
+        .as_tree()
+        .vistr()
+        .dfs_preorder_iter()
+        .cloned()
+        .collect();
+    assert_eq!(pre, ["0", "1", "2", "3", "4", "5", "6"]);
+    let ino: Vec<_> = cached
+        .as_tree()
+        .vistr()
+        .dfs_inorder_iter()
+        .cloned()
+        .collect();
+    assert_eq!(ino, ["2", "1", "3", "0", "5", "4", "6"]);
+}
+
+#[test]
+fn from_sorted() {
+    use compt::dfs_order::*;
+    let k = CompleteTreeContainer::from_sorted(vec![1, 1, 2, 3, 5, 8, 13]).unwrap();
+    assert!(k.as_tree().is_bst());
+    assert_eq!(
+        *k.as_tree()
+            .vistr()
+            .descend(|a| if **a > 4 { Branch::Left } else { Branch::Right }),
+        5
+    );
+
+    assert!(matches!(
+        CompleteTreeContainer::from_sorted(vec![1, 3, 2]),
+        Err(FromSortedError::NotSorted)
+    ));
+    match CompleteTreeContainer::from_sorted(vec![1, 2, 3, 4]) {
+        Err(FromSortedError::NotCompleteTreeSize(e)) => assert_eq!(e.length, 4),
+        _ => panic!("expected a length error"),
+    }
+}
+
+#[test]
+fn zip_depth_map() {
+    let k1 =
+        compt::dfs_order::CompleteTreeContainer::from_preorder((0..7).collect::<Vec<_>>()).unwrap();
+    let k2 = compt::dfs_order::CompleteTreeContainer::from_preorder((10..17).collect::<Vec<_>>())
+        .unwrap();
+
+    let mapped = k1
+        .as_tree()
+        .vistr()
+        .zip_depth_map(k2.as_tree().vistr(), |d, a, b| d.0 * 100 + a + b);
+    assert_eq!(mapped.get_height(), 3);
+    let mapped: Vec<_> = mapped.dfs_preorder_iter().collect();
+
+    let composed: Vec<_> = k1
+        .as_tree()
+        .vistr()
+        .zip(k2.as_tree().vistr())
+        .with_depth(Depth(0))
+        .map(|(d, (a, b))| d.0 * 100 + a + b)
+        .dfs_preorder_iter()
+        .collect();
+    assert_eq!(mapped, composed);
+    assert_eq!(mapped, [10, 112, 214, 216, 118, 220, 222]);
+}
+
+#[test]
+fn count_below_depth() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder(vec![0; 15]).unwrap();
+    assert_eq!(k.as_tree().vistr().count_below_depth(1), 12);
+    assert_eq!(k.as_tree().vistr().count_below_depth(0), 14);
+    assert_eq!(k.as_tree().vistr().count_below_depth(3), 0);
+    assert_eq!(k.as_tree().vistr().count_below_depth(usize::MAX), 0);
+
+    //No exact hint, so these walk the tree.
+    assert_eq!(Lopsided(3).count_below_depth(1), 4);
+    assert_eq!(Lopsided(5).take(2).count_below_depth(0), 4);
+}
+
+#[test]
+fn map_until_depth() {
+    let k = compt::dfs_order::CompleteTreeContainer::from_preorder((0..15).collect::<Vec<_>>())
+        .unwrap();
+    let v = k.as_tree().vistr().map_until_depth(2, |a| a * 10);
+    assert_eq!(v.level_remaining_hint(), (3, Some(3)));
+    let res: Vec<_> = v.dfs_preorder_iter().collect();
+    assert_eq!(res, [0, 10, 20, 50, 80, 90, 120]);
+}